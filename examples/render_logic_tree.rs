@@ -61,5 +61,12 @@ fn main() {
     let mut f = std::fs::File::create("logic_tree_remove_none.dot").unwrap();
     dot::render(&term_without_none, &mut f).unwrap();
 
+    // the services shared by many dependents are drawn once in the service
+    // graph
+    let mut graph = ServiceGraph::default();
+    graph.add(&svc_f);
+    let mut f = std::fs::File::create("service_graph.dot").unwrap();
+    dot::render(&graph, &mut f).unwrap();
+
     println!("probability result: {}", term.calc());
 }
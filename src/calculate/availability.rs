@@ -0,0 +1,161 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::time::Duration;
+
+use super::{Bdd, Term};
+
+// the billing month of most SLAs
+pub const MONTH: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+pub const QUARTER: Duration = Duration::from_secs(3 * 30 * 24 * 60 * 60);
+pub const YEAR: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+// Availability is represented by the unavailability, so that the availability
+// close to 1 doesn't lose its significant digits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Availability {
+    unavailability: f64,
+}
+
+impl Availability {
+    pub fn from_unavailability(unavailability: f64) -> Availability {
+        Availability { unavailability }
+    }
+
+    pub fn from_probability(probability: f64) -> Availability {
+        Availability {
+            unavailability: 1.0 - probability,
+        }
+    }
+
+    pub fn availability(&self) -> f64 {
+        1.0 - self.unavailability
+    }
+
+    /// Get the unavailability.
+    pub fn unavailability(&self) -> f64 {
+        self.unavailability
+    }
+
+    // nines returns the number of nines, e.g. 99.99% is 4 nines
+    pub fn nines(&self) -> f64 {
+        -self.unavailability.log10()
+    }
+
+    // downtime returns the expected downtime in the period
+    pub fn downtime(&self, period: Duration) -> Duration {
+        period.mul_f64(self.unavailability)
+    }
+
+    pub fn downtime_per_month(&self) -> Duration {
+        self.downtime(MONTH)
+    }
+
+    pub fn downtime_per_year(&self) -> Duration {
+        self.downtime(YEAR)
+    }
+
+    // percent prints the availability with a digit after its nines, so a
+    // highly available component is not rounded to 100%
+    pub fn percent(&self) -> String {
+        let nines = (self.nines() + 1e-9).floor();
+        let digits = (nines as usize).saturating_sub(1).clamp(2, 12);
+        format!("{:.*}%", digits, self.availability() * 100.0)
+    }
+}
+
+// format_duration prints the duration in the largest units, like "4h 23m 0s"
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    if seconds < 60.0 {
+        return format!("{:.2}s", seconds);
+    }
+
+    let seconds = seconds.round() as u64;
+    let (days, hours, minutes, seconds) = (
+        seconds / 86400,
+        seconds % 86400 / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+    );
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {}s", minutes, seconds)
+    }
+}
+
+impl Term {
+    // unavailability calculates 1 - probability of the term in the
+    // unavailability space, without catastrophic cancellation
+    pub fn unavailability(&self) -> f64 {
+        if self.is_conditional() {
+            return self.condition(|term| term.unavailability());
+        }
+
+        Bdd::compile(self).unavailability()
+    }
+
+    pub fn availability(&self) -> Availability {
+        Availability::from_unavailability(self.unavailability())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use float_cmp::approx_eq;
+
+    use crate::calculate::{AtomRegistry, Availability, Engine, Term};
+
+    #[test]
+    fn test_availability() {
+        let mut registry = AtomRegistry::default();
+        let replicas: Vec<Term> = (0..3)
+            .map(|index| Term::atom(registry.new_atom(format!("replica_{}", index), 0.99999)))
+            .collect();
+
+        // the unavailability is 1e-15, which is totally lost in the
+        // availability space
+        let term = Term::union(replicas);
+        let expected = (1.0 - 0.99999f64).powi(3);
+        assert!(approx_eq!(f64, term.unavailability(), expected, ulps = 10));
+        assert!(!approx_eq!(
+            f64,
+            1.0 - term.calc_with(Engine::InclusionExclusion),
+            expected,
+            ulps = 10
+        ));
+
+        let availability = term.availability();
+        assert!(approx_eq!(
+            f64,
+            availability.nines(),
+            15.0,
+            epsilon = 0.000001
+        ));
+
+        let availability = Availability::from_unavailability(0.001);
+        assert!(approx_eq!(
+            f64,
+            availability.downtime_per_month().as_secs_f64(),
+            Duration::from_secs(43 * 60 + 12).as_secs_f64(),
+            epsilon = 0.001
+        ));
+    }
+}
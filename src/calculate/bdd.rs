@@ -0,0 +1,365 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{collections::HashMap, sync::Arc};
+
+use super::{Atom, MultiOp, Term, UnaryOp};
+
+type NodeId = usize;
+
+const FALSE: NodeId = 0;
+const TRUE: NodeId = 1;
+
+// the level of the terminal nodes, which is larger than any variable
+const TERMINAL: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Node {
+    level: usize,
+    low: NodeId,
+    high: NodeId,
+}
+
+#[derive(Clone, Copy)]
+enum BinaryOp {
+    And,
+    Or,
+}
+
+// Bdd is a reduced ordered binary decision diagram of a term. Every atom is a
+// variable, and the variables are ordered by their first appearance in the
+// term, which usually keeps the atoms of a sub-term close to each other.
+//
+// The probability of a term can be calculated in time linear in the size of
+// the diagram, as the two branches of a node are disjoint events.
+pub struct Bdd {
+    variables: Vec<Arc<Atom>>,
+    nodes: Vec<Node>,
+    unique: HashMap<Node, NodeId>,
+    root: NodeId,
+}
+
+impl Bdd {
+    pub fn compile(term: &Term) -> Bdd {
+        let mut bdd = Bdd {
+            variables: Vec::new(),
+            nodes: vec![
+                Node {
+                    level: TERMINAL,
+                    low: FALSE,
+                    high: FALSE,
+                },
+                Node {
+                    level: TERMINAL,
+                    low: TRUE,
+                    high: TRUE,
+                },
+            ],
+            unique: HashMap::new(),
+            root: FALSE,
+        };
+
+        let mut levels = HashMap::new();
+        bdd.root = bdd.build(term, &mut levels);
+        bdd
+    }
+
+    /// Get the number of nodes in the diagram, including the two terminals.
+    pub fn size(&self) -> usize {
+        2 + self.reachable()[2..]
+            .iter()
+            .filter(|reachable| **reachable)
+            .count()
+    }
+
+    // the diagram may contain intermediate nodes which are not reachable from
+    // the root, they should be skipped
+    fn reachable(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut stack = vec![self.root];
+        while let Some(id) = stack.pop() {
+            if !reachable[id] {
+                reachable[id] = true;
+                stack.extend([self.nodes[id].low, self.nodes[id].high]);
+            }
+        }
+        reachable
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.fold(0f64, 1f64, |atom, low, high| {
+            atom.probability() * high + atom.unavailability() * low
+        })
+    }
+
+    // unavailability calculates 1 - probability without subtracting from 1,
+    // every step is a sum of non-negative numbers, so the precision is kept
+    // even if the probability is very close to 1
+    pub fn unavailability(&self) -> f64 {
+        self.fold(1f64, 0f64, |atom, low, high| {
+            atom.probability() * high + atom.unavailability() * low
+        })
+    }
+
+    // fold evaluates the diagram from the terminals to the root, every node is
+    // evaluated only once
+    pub(crate) fn fold<T: Clone, F: FnMut(&Arc<Atom>, &T, &T) -> T>(
+        &self,
+        on_false: T,
+        on_true: T,
+        mut f: F,
+    ) -> T {
+        let reachable = self.reachable();
+
+        let mut values: Vec<Option<T>> = vec![None; self.nodes.len()];
+        values[FALSE] = Some(on_false);
+        values[TRUE] = Some(on_true);
+
+        // a node is always created after its children, so the values of the
+        // children are ready when the node is visited in order
+        for id in (2..self.nodes.len()).filter(|id| reachable[*id]) {
+            let node = self.nodes[id];
+            let value = f(
+                &self.variables[node.level],
+                values[node.low].as_ref().unwrap(),
+                values[node.high].as_ref().unwrap(),
+            );
+            values[id] = Some(value);
+        }
+
+        values[self.root].take().unwrap()
+    }
+
+    fn build(&mut self, term: &Term, levels: &mut HashMap<String, usize>) -> NodeId {
+        match term {
+            // none is always satisfied, like an empty intersect
+            Term::None => TRUE,
+            Term::Unary { atom, op } => {
+                let level = *levels.entry(atom.name().to_string()).or_insert_with(|| {
+                    self.variables.push(atom.clone());
+                    self.variables.len() - 1
+                });
+
+                match op {
+                    UnaryOp::None => self.mk(level, FALSE, TRUE),
+                    UnaryOp::Not => self.mk(level, TRUE, FALSE),
+                }
+            }
+            Term::Multiple { terms, op } => {
+                // an empty union or intersect is always satisfied like none,
+                // the same as `remove_none`
+                if terms.is_empty() {
+                    return TRUE;
+                }
+
+                let (op, mut result) = match op {
+                    MultiOp::Union => (BinaryOp::Or, FALSE),
+                    MultiOp::Intersect => (BinaryOp::And, TRUE),
+                };
+
+                // none is built as true, so it satisfies a union, and is
+                // ignored by an intersect
+                for term in terms.iter() {
+                    let operand = self.build(term, levels);
+                    result = self.apply(op, result, operand, &mut HashMap::new());
+                }
+
+                result
+            }
+            Term::Threshold { terms, k } => {
+                // none is always satisfied
+                let operands: Vec<NodeId> = terms
+                    .iter()
+                    .map(|term| {
+                        if term.is_none() {
+                            TRUE
+                        } else {
+                            self.build(term, levels)
+                        }
+                    })
+                    .collect();
+
+                self.threshold(&operands, *k, &mut HashMap::new())
+            }
+        }
+    }
+
+    // threshold builds the diagram of "at least k of the operands", by
+    // deciding the operands one by one
+    fn threshold(
+        &mut self,
+        operands: &[NodeId],
+        k: usize,
+        memo: &mut HashMap<(usize, usize), NodeId>,
+    ) -> NodeId {
+        if k == 0 {
+            return TRUE;
+        }
+        if operands.len() < k {
+            return FALSE;
+        }
+        if let Some(id) = memo.get(&(operands.len(), k)) {
+            return *id;
+        }
+
+        let (first, rest) = (operands[0], &operands[1..]);
+        let satisfied = self.threshold(rest, k - 1, memo);
+        let unsatisfied = self.threshold(rest, k, memo);
+
+        // (first & satisfied) | (!first & unsatisfied)
+        let not_first = self.not(first, &mut HashMap::new());
+        let satisfied = self.apply(BinaryOp::And, first, satisfied, &mut HashMap::new());
+        let unsatisfied = self.apply(BinaryOp::And, not_first, unsatisfied, &mut HashMap::new());
+        let id = self.apply(BinaryOp::Or, satisfied, unsatisfied, &mut HashMap::new());
+
+        memo.insert((operands.len(), k), id);
+        id
+    }
+
+    fn not(&mut self, id: NodeId, memo: &mut HashMap<NodeId, NodeId>) -> NodeId {
+        match id {
+            FALSE => return TRUE,
+            TRUE => return FALSE,
+            _ => {}
+        }
+        if let Some(id) = memo.get(&id) {
+            return *id;
+        }
+
+        let node = self.nodes[id];
+        let low = self.not(node.low, memo);
+        let high = self.not(node.high, memo);
+        let result = self.mk(node.level, low, high);
+
+        memo.insert(id, result);
+        result
+    }
+
+    fn mk(&mut self, level: usize, low: NodeId, high: NodeId) -> NodeId {
+        if low == high {
+            return low;
+        }
+
+        let node = Node { level, low, high };
+        if let Some(id) = self.unique.get(&node) {
+            return *id;
+        }
+
+        self.nodes.push(node);
+        let id = self.nodes.len() - 1;
+        self.unique.insert(node, id);
+        id
+    }
+
+    fn apply(
+        &mut self,
+        op: BinaryOp,
+        a: NodeId,
+        b: NodeId,
+        memo: &mut HashMap<(NodeId, NodeId), NodeId>,
+    ) -> NodeId {
+        match (op, a, b) {
+            (BinaryOp::And, FALSE, _) | (BinaryOp::And, _, FALSE) => return FALSE,
+            (BinaryOp::And, TRUE, other) | (BinaryOp::And, other, TRUE) => return other,
+            (BinaryOp::Or, TRUE, _) | (BinaryOp::Or, _, TRUE) => return TRUE,
+            (BinaryOp::Or, FALSE, other) | (BinaryOp::Or, other, FALSE) => return other,
+            _ => {}
+        }
+        if a == b {
+            return a;
+        }
+        if let Some(id) = memo.get(&(a, b)) {
+            return *id;
+        }
+
+        let (node_a, node_b) = (self.nodes[a], self.nodes[b]);
+        let level = node_a.level.min(node_b.level);
+        let (a_low, a_high) = if node_a.level == level {
+            (node_a.low, node_a.high)
+        } else {
+            (a, a)
+        };
+        let (b_low, b_high) = if node_b.level == level {
+            (node_b.low, node_b.high)
+        } else {
+            (b, b)
+        };
+
+        let low = self.apply(op, a_low, b_low, memo);
+        let high = self.apply(op, a_high, b_high, memo);
+        let id = self.mk(level, low, high);
+
+        memo.insert((a, b), id);
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+    use rand::Rng;
+
+    use crate::calculate::{AtomRegistry, Engine, Term};
+
+    use super::Bdd;
+
+    #[test]
+    fn test_bdd() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..5 {
+            let mut registry = AtomRegistry::default();
+            let atom_a = registry.new_atom("atom_a".to_owned(), rng.gen());
+            let atom_b = registry.new_atom("atom_b".to_owned(), rng.gen());
+            let atom_c = registry.new_atom("atom_c".to_owned(), rng.gen());
+
+            let mut not_c = Term::atom(atom_c.clone());
+            not_c.not();
+            let term = Term::union(vec![
+                Term::intersect(vec![Term::atom(atom_a.clone()), Term::atom(atom_b.clone())]),
+                Term::intersect(vec![Term::atom(atom_b), Term::atom(atom_c)]),
+                Term::intersect(vec![Term::atom(atom_a), not_c]),
+            ]);
+
+            let expected = term.calc_with(Engine::InclusionExclusion);
+            let got = term.calc_with(Engine::Bdd);
+            assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64));
+        }
+    }
+
+    #[test]
+    fn test_bdd_reduced() {
+        let mut registry = AtomRegistry::default();
+        let atoms: Vec<Term> = (0..32)
+            .map(|index| Term::atom(registry.new_atom(format!("atom_{}", index), 0.99)))
+            .collect();
+
+        // the union of the same atoms is reduced to a single chain
+        let term = Term::intersect(vec![Term::union(atoms.clone()), Term::union(atoms)]);
+        let bdd = Bdd::compile(&term);
+        assert_eq!(bdd.size(), 32 + 2);
+        assert!(approx_eq!(
+            f64,
+            bdd.probability(),
+            1.0 - 0.01f64.powi(32),
+            epsilon = 0.0000001f64
+        ));
+        assert!(approx_eq!(
+            f64,
+            bdd.unavailability(),
+            0.01f64.powi(32),
+            ulps = 1000
+        ));
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::time::Duration;
+
+use crate::error::{check_sla, SlacError};
+
+use super::{Importance, Term};
+
+// ErrorBudget compares the theoretical availability of a design with the
+// target SLO. The budget is the downtime allowed by the target, and the
+// design consumes its expected downtime.
+#[derive(Debug, Clone)]
+pub struct ErrorBudget {
+    target: f64,
+    unavailability: f64,
+
+    // the atoms sorted by their contribution, from the largest one
+    consumers: Vec<Importance>,
+}
+
+impl ErrorBudget {
+    /// Get the target availability.
+    pub fn target(&self) -> f64 {
+        self.target
+    }
+
+    /// Get the unavailability of the design.
+    pub fn unavailability(&self) -> f64 {
+        self.unavailability
+    }
+
+    // is_met returns whether the theoretical availability reaches the target
+    pub fn is_met(&self) -> bool {
+        self.unavailability <= 1.0 - self.target
+    }
+
+    // budget returns the downtime allowed in the period
+    pub fn budget(&self, period: Duration) -> Duration {
+        period.mul_f64(1.0 - self.target)
+    }
+
+    // consumed returns the expected downtime of the design in the period
+    pub fn consumed(&self, period: Duration) -> Duration {
+        period.mul_f64(self.unavailability)
+    }
+
+    // consumption returns the fraction of the budget consumed by the design,
+    // which is larger than 1 if the target is not met
+    pub fn consumption(&self) -> f64 {
+        self.unavailability / (1.0 - self.target)
+    }
+
+    /// Get a reference to the consumers, from the largest one.
+    pub fn consumers(&self) -> &[Importance] {
+        self.consumers.as_ref()
+    }
+}
+
+impl Term {
+    // error_budget calculates the error budget of the term with the target,
+    // and attributes the consumption to the atoms by their contributions. The
+    // target should be in [0, 1).
+    pub fn try_error_budget(&self, target: f64) -> Result<ErrorBudget, SlacError> {
+        check_sla("target", target)?;
+        if target >= 1.0 {
+            return Err(SlacError::InvalidTarget { target });
+        }
+
+        let mut consumers = self.importance();
+        consumers.sort_by(|a, b| b.contribution().total_cmp(&a.contribution()));
+
+        Ok(ErrorBudget {
+            target,
+            unavailability: self.unavailability(),
+            consumers,
+        })
+    }
+
+    pub fn error_budget(&self, target: f64) -> ErrorBudget {
+        self.try_error_budget(target)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use crate::{
+        calculate::{AtomRegistry, Term, MONTH},
+        error::SlacError,
+    };
+
+    #[test]
+    fn test_error_budget() {
+        let mut registry = AtomRegistry::default();
+        let infra = registry.new_atom("infra".to_owned(), 0.9995);
+        let network = registry.new_atom("network".to_owned(), 0.9999);
+        let term = Term::intersect(vec![Term::atom(infra), Term::atom(network)]);
+
+        let budget = term.error_budget(0.999);
+        assert!(budget.is_met());
+        // 43.2 minutes in a month
+        assert!(approx_eq!(
+            f64,
+            budget.budget(MONTH).as_secs_f64(),
+            43.2 * 60.0,
+            epsilon = 0.001
+        ));
+        assert!(approx_eq!(
+            f64,
+            budget.consumption(),
+            (1.0 - 0.9995 * 0.9999) / 0.001,
+            epsilon = 0.0000001f64
+        ));
+
+        let consumers = budget.consumers();
+        assert_eq!(consumers[0].atom().name(), "infra");
+        assert!(approx_eq!(
+            f64,
+            consumers[0].contribution(),
+            0.0005 * 0.9999,
+            epsilon = 0.0000001f64
+        ));
+
+        assert!(!term.error_budget(0.9999).is_met());
+
+        assert_eq!(
+            term.try_error_budget(1.0).err(),
+            Some(SlacError::InvalidTarget { target: 1.0 })
+        );
+        assert!(matches!(
+            term.try_error_budget(1.5),
+            Err(SlacError::InvalidSla { .. })
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::{Add, Mul, Sub},
+    sync::Arc,
+};
+
+use crate::error::{check_sla, SlacError};
+
+use super::{Atom, Term};
+
+// Condition is the conditional probability table of an atom given its
+// parents. The i-th entry of the table is the probability of the atom when
+// the j-th parent is available iff the j-th bit of i is set, so the parents
+// and their dependent atoms form a small Bayesian network.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Condition {
+    parents: Vec<Arc<Atom>>,
+    table: Vec<f64>,
+}
+
+impl Condition {
+    pub(crate) fn new(
+        name: &str,
+        parents: Vec<Arc<Atom>>,
+        table: Vec<f64>,
+    ) -> Result<Condition, SlacError> {
+        if table.len() != 1 << parents.len() {
+            return Err(SlacError::InvalidTable {
+                name: name.to_string(),
+                expected: 1 << parents.len(),
+                got: table.len(),
+            });
+        }
+        for probability in table.iter() {
+            check_sla(name, *probability)?;
+        }
+
+        Ok(Condition { parents, table })
+    }
+
+    /// Get a reference to the parents.
+    pub fn parents(&self) -> &[Arc<Atom>] {
+        self.parents.as_ref()
+    }
+
+    /// Get a reference to the conditional probability table.
+    pub fn table(&self) -> &[f64] {
+        self.table.as_ref()
+    }
+
+    // probability returns the probability of the atom, with the state of
+    // every parent given by `state`
+    pub fn probability<F: Fn(&str) -> bool>(&self, state: F) -> f64 {
+        let index = self
+            .parents
+            .iter()
+            .enumerate()
+            .filter(|(_, parent)| state(parent.name()))
+            .fold(0, |index, (bit, _)| index | 1 << bit);
+        self.table[index]
+    }
+}
+
+impl Term {
+    pub fn is_conditional(&self) -> bool {
+        self.atoms().iter().any(|atom| atom.condition().is_some())
+    }
+
+    // network returns the atoms in this term and all of their ancestors, and
+    // every atom is placed after its parents. An atom in this term is preferred
+    // to the parent with the same name, so a forced atom is respected.
+    pub(crate) fn network(&self) -> Vec<Arc<Atom>> {
+        fn visit(
+            atom: &Arc<Atom>,
+            atoms: &HashMap<String, Arc<Atom>>,
+            visited: &mut HashSet<String>,
+            network: &mut Vec<Arc<Atom>>,
+        ) {
+            let atom = atoms.get(atom.name()).unwrap_or(atom);
+            if !visited.insert(atom.name().to_string()) {
+                return;
+            }
+
+            if let Some(condition) = atom.condition() {
+                for parent in condition.parents() {
+                    visit(parent, atoms, visited, network);
+                }
+            }
+            network.push(atom.clone());
+        }
+
+        let atoms = self.atoms();
+        let index: HashMap<String, Arc<Atom>> = atoms
+            .iter()
+            .map(|atom| (atom.name().to_string(), atom.clone()))
+            .collect();
+
+        let mut network = Vec::new();
+        let mut visited = HashSet::new();
+        for atom in atoms.iter() {
+            visit(atom, &index, &mut visited, &mut network);
+        }
+        network
+    }
+
+    // condition enumerates every assignment of the atoms which others depend
+    // on. In each assignment, the other atoms are independent, so the term is
+    // replaced by a term without conditional atoms and calculated by `calc`.
+    // The result is the sum of them weighted by the probability of the
+    // assignment.
+    pub(crate) fn condition<F: FnMut(&Term) -> f64>(&self, calc: F) -> f64 {
+        self.condition_with(|probability| probability, Atom::probability, calc)
+    }
+
+    // condition_with is `condition` in another number type, e.g. the rational
+    // numbers. `convert` converts a probability in a table into the type, and
+    // `probability_of` returns the probability of an unconditional atom.
+    pub(crate) fn condition_with<T, P, A, F>(&self, convert: P, probability_of: A, mut calc: F) -> T
+    where
+        T: Clone + PartialEq + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+        P: Fn(f64) -> T,
+        A: Fn(&Atom) -> T,
+        F: FnMut(&Term) -> T,
+    {
+        let (zero, one) = (convert(0.0), convert(1.0));
+        let network = self.network();
+        let parents: HashSet<&str> = network
+            .iter()
+            .filter_map(|atom| atom.condition())
+            .flat_map(|condition| condition.parents().iter().map(|parent| parent.name()))
+            .collect();
+        // the parents of a conditioned atom are also conditioned atoms, as
+        // they are parents of a conditional atom
+        let conditioned: Vec<&Arc<Atom>> = network
+            .iter()
+            .filter(|atom| parents.contains(atom.name()))
+            .collect();
+
+        let mut sum = zero.clone();
+        let mut state: HashMap<&str, bool> = HashMap::new();
+        for assignment in 0..1usize << conditioned.len() {
+            let mut weight = one.clone();
+            for (bit, atom) in conditioned.iter().enumerate() {
+                let up = assignment & 1 << bit != 0;
+                let probability = match atom.condition() {
+                    Some(condition) => convert(condition.probability(|name| state[name])),
+                    None => probability_of(atom),
+                };
+
+                weight = weight
+                    * if up {
+                        probability
+                    } else {
+                        one.clone() - probability
+                    };
+                state.insert(atom.name(), up);
+            }
+            if weight == zero {
+                continue;
+            }
+
+            let term = self.substitute(&|atom: &Atom| match state.get(atom.name()) {
+                Some(up) => Some(if *up { 1.0 } else { 0.0 }),
+                None => atom
+                    .condition()
+                    .map(|condition| condition.probability(|name| state[name])),
+            });
+            sum = sum + weight * calc(&term);
+        }
+        sum
+    }
+
+    // substitute replaces the probability of the atoms, and the replaced atoms
+    // are independent
+    fn substitute<F: Fn(&Atom) -> Option<f64>>(&self, probability: &F) -> Term {
+        match self {
+            Term::None => Term::None,
+            Term::Unary { atom, op } => match probability(atom) {
+                Some(probability) => Term::Unary {
+                    atom: Arc::new(Atom::new(atom.name().to_string(), probability)),
+                    op: *op,
+                },
+                None => Term::Unary {
+                    atom: atom.clone(),
+                    op: *op,
+                },
+            },
+            Term::Multiple { terms, op } => Term::Multiple {
+                terms: terms
+                    .iter()
+                    .map(|term| term.substitute(probability))
+                    .collect(),
+                op: *op,
+            },
+            Term::Threshold { terms, k } => Term::Threshold {
+                terms: terms
+                    .iter()
+                    .map(|term| term.substitute(probability))
+                    .collect(),
+                k: *k,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use crate::calculate::{AtomRegistry, Engine, Term};
+
+    #[test]
+    fn test_condition() {
+        let mut registry = AtomRegistry::default();
+        let database = registry.new_atom("database".to_owned(), 0.9);
+        // the cache is more likely down when the database is down
+        let cache = registry.new_conditional_atom(
+            "cache".to_owned(),
+            vec![database.clone()],
+            vec![0.5, 0.99],
+        );
+        assert!(approx_eq!(
+            f64,
+            cache.probability(),
+            0.1 * 0.5 + 0.9 * 0.99,
+            epsilon = 0.0000001f64
+        ));
+
+        // the request is served if either of them is available
+        let term = Term::union(vec![Term::atom(database), Term::atom(cache.clone())]);
+        let expected = 1.0 - 0.1 * 0.5;
+        for engine in [Engine::InclusionExclusion, Engine::Bdd] {
+            assert!(approx_eq!(
+                f64,
+                term.calc_with(engine),
+                expected,
+                epsilon = 0.0000001f64
+            ));
+        }
+        assert!(approx_eq!(
+            f64,
+            term.unavailability(),
+            0.1 * 0.5,
+            epsilon = 0.0000001f64
+        ));
+
+        // the database is not in the term, but still conditioned on
+        let term = Term::atom(cache);
+        let estimate = term.estimate_with_seed(100_000, 0);
+        let (low, high) = estimate.confidence_interval();
+        assert!(low <= term.calc() && term.calc() <= high);
+    }
+}
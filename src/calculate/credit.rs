@@ -0,0 +1,151 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use crate::error::SlacError;
+
+use super::Downtime;
+
+// CreditTier pays `credit` (a fraction of the bill) when the availability of a
+// billing period is lower than `below`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreditTier {
+    below: f64,
+    credit: f64,
+}
+
+impl CreditTier {
+    pub fn new(below: f64, credit: f64) -> Result<CreditTier, SlacError> {
+        if !(0.0..=1.0).contains(&below) || !(0.0..=1.0).contains(&credit) {
+            return Err(SlacError::InvalidCreditTier { below, credit });
+        }
+        Ok(CreditTier { below, credit })
+    }
+
+    /// Get the availability threshold of the tier.
+    pub fn below(&self) -> f64 {
+        self.below
+    }
+
+    /// Get the credit of the tier.
+    pub fn credit(&self) -> f64 {
+        self.credit
+    }
+}
+
+// CreditPolicy is a multiple-steps SLA, e.g. 10% credit below 99.99% and 25%
+// credit below 99%. Only the lowest tier a period falls in is paid.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreditPolicy {
+    // sorted by the threshold, from the highest one
+    tiers: Vec<CreditTier>,
+}
+
+impl CreditPolicy {
+    pub fn new(mut tiers: Vec<CreditTier>) -> CreditPolicy {
+        tiers.sort_by(|a, b| b.below.total_cmp(&a.below));
+        CreditPolicy { tiers }
+    }
+
+    /// Get a reference to the tiers, from the highest threshold.
+    pub fn tiers(&self) -> &[CreditTier] {
+        self.tiers.as_ref()
+    }
+
+    // tier returns the index of the tier which the availability falls in
+    pub fn tier(&self, availability: f64) -> Option<usize> {
+        self.tiers
+            .iter()
+            .rposition(|tier| availability < tier.below)
+    }
+
+    pub fn credit(&self, availability: f64) -> f64 {
+        self.tier(availability)
+            .map(|index| self.tiers[index].credit)
+            .unwrap_or(0.0)
+    }
+}
+
+impl Downtime {
+    fn availabilities(&self) -> impl Iterator<Item = f64> + '_ {
+        let period = self.period().as_secs_f64();
+        self.samples()
+            .iter()
+            .map(move |downtime| 1.0 - downtime.as_secs_f64() / period)
+    }
+
+    // tier_probabilities returns the probability of a period falling in each
+    // tier of the policy
+    pub fn tier_probabilities(&self, policy: &CreditPolicy) -> Vec<f64> {
+        let mut counts = vec![0usize; policy.tiers().len()];
+        for availability in self.availabilities() {
+            if let Some(index) = policy.tier(availability) {
+                counts[index] += 1;
+            }
+        }
+
+        let samples = self.samples().len().max(1) as f64;
+        counts
+            .into_iter()
+            .map(|count| count as f64 / samples)
+            .collect()
+    }
+
+    // expected_credit returns the expected credit of a period, as a fraction
+    // of the bill
+    pub fn expected_credit(&self, policy: &CreditPolicy) -> f64 {
+        let total: f64 = self
+            .availabilities()
+            .map(|availability| policy.credit(availability))
+            .sum();
+        total / self.samples().len().max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use crate::calculate::{AtomRegistry, CreditPolicy, CreditTier, DowntimeOptions, Term};
+
+    #[test]
+    fn test_credit() {
+        let policy = CreditPolicy::new(vec![
+            CreditTier::new(0.99, 0.25).unwrap(),
+            CreditTier::new(0.9999, 0.1).unwrap(),
+        ]);
+        assert_eq!(policy.credit(0.99999), 0.0);
+        assert_eq!(policy.credit(0.995), 0.1);
+        assert_eq!(policy.credit(0.98), 0.25);
+        assert!(CreditTier::new(1.5, 0.1).is_err());
+
+        let mut registry = AtomRegistry::default();
+        let term = Term::atom(registry.new_atom("compute".to_owned(), 0.999));
+        let downtime = term.simulate_downtime_with_seed(10_000, &DowntimeOptions::default(), 0);
+
+        let probabilities = downtime.tier_probabilities(&policy);
+        let expected = probabilities[0] * 0.1 + probabilities[1] * 0.25;
+        assert!(approx_eq!(
+            f64,
+            downtime.expected_credit(&policy),
+            expected,
+            epsilon = 0.0000001f64
+        ));
+        // about 0.72 outages of an hour in a month, so about half of the
+        // periods have an outage and breach 99.99%
+        assert!(probabilities[0] > 0.4 && probabilities[0] < 0.6);
+    }
+}
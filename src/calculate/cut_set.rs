@@ -0,0 +1,239 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use super::{Atom, MultiOp, Term, UnaryOp};
+
+// CutSet is a set of atoms whose simultaneous failure makes the term false
+#[derive(Debug, Clone)]
+pub struct CutSet {
+    // sorted by the name
+    atoms: Vec<Arc<Atom>>,
+}
+
+impl CutSet {
+    /// Get a reference to the atoms in the cut set.
+    pub fn atoms(&self) -> &[Arc<Atom>] {
+        self.atoms.as_ref()
+    }
+
+    // probability returns the probability that every atom in the cut set fails
+    pub fn probability(&self) -> f64 {
+        self.atoms
+            .iter()
+            .map(|atom| 1.0 - atom.probability())
+            .product()
+    }
+}
+
+// Family is a set of cut sets, every cut set is represented by the sorted
+// names of the atoms
+type Family = Vec<Vec<String>>;
+
+// minimize removes every cut set which is a superset of another one
+fn minimize(mut family: Family) -> Family {
+    family.sort_by(|a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+    family.dedup();
+
+    let mut minimal: Family = Vec::new();
+    for set in family {
+        let absorbed = minimal
+            .iter()
+            .any(|small| small.iter().all(|name| set.binary_search(name).is_ok()));
+        if !absorbed {
+            minimal.push(set);
+        }
+    }
+    minimal
+}
+
+// product returns every union of a cut set from `a` and a cut set from `b`
+fn product(a: &Family, b: &Family, max_order: usize) -> Family {
+    let mut family = Vec::new();
+    for set_a in a {
+        for set_b in b {
+            let mut set: Vec<String> = set_a.iter().chain(set_b.iter()).cloned().collect();
+            set.sort();
+            set.dedup();
+            if set.len() <= max_order {
+                family.push(set);
+            }
+        }
+    }
+    minimize(family)
+}
+
+// at_least returns the cut sets in which at least k of the families fail
+fn at_least(
+    families: &[Family],
+    k: usize,
+    max_order: usize,
+    memo: &mut HashMap<(usize, usize), Family>,
+) -> Family {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    if families.len() < k {
+        return Vec::new();
+    }
+    if let Some(family) = memo.get(&(families.len(), k)) {
+        return family.clone();
+    }
+
+    // either the first one fails with k - 1 of the others, or k of the others
+    // fail
+    let rest = &families[1..];
+    let mut family = product(
+        &families[0],
+        &at_least(rest, k - 1, max_order, memo),
+        max_order,
+    );
+    family.extend(at_least(rest, k, max_order, memo));
+    let family = minimize(family);
+
+    memo.insert((families.len(), k), family.clone());
+    family
+}
+
+// failures returns the minimal combinations of failures which satisfy the
+// term. The term is expected to be the negation of an availability term, so a
+// negated atom is a failure.
+//
+// A combination which also requires some atom to be available is ignored, which
+// never happens for the terms dumped from the sla model.
+fn failures(term: &Term, max_order: usize) -> Family {
+    match term {
+        Term::None => unreachable!(),
+        Term::Unary {
+            atom,
+            op: UnaryOp::Not,
+        } => vec![vec![atom.name().to_string()]],
+        Term::Unary {
+            atom: _,
+            op: UnaryOp::None,
+        } => Vec::new(),
+        Term::Multiple {
+            terms,
+            op: MultiOp::Union,
+        } => minimize(
+            terms
+                .iter()
+                .flat_map(|term| failures(term, max_order))
+                .collect(),
+        ),
+        Term::Multiple {
+            terms,
+            op: MultiOp::Intersect,
+        } => terms.iter().fold(vec![Vec::new()], |family, term| {
+            product(&family, &failures(term, max_order), max_order)
+        }),
+        Term::Threshold { terms, k } => {
+            let families: Vec<Family> =
+                terms.iter().map(|term| failures(term, max_order)).collect();
+            at_least(&families, *k, max_order, &mut HashMap::new())
+        }
+    }
+}
+
+impl Term {
+    // minimal_cut_sets returns the smallest sets of atoms whose simultaneous
+    // failure makes the term false, ranked by the probability. The cut sets
+    // with more than `max_order` atoms are skipped, which saves a lot of time
+    // for large terms.
+    pub fn minimal_cut_sets(&self, max_order: Option<usize>) -> Vec<CutSet> {
+        let mut failure = match self.clone().remove_none() {
+            Some(term) => term,
+            None => return Vec::new(),
+        };
+        failure.not();
+        let failure = failure.flat();
+
+        let atoms: BTreeMap<String, Arc<Atom>> = self
+            .atoms()
+            .into_iter()
+            .map(|atom| (atom.name().to_string(), atom))
+            .collect();
+
+        let mut cut_sets: Vec<CutSet> = failures(&failure, max_order.unwrap_or(usize::MAX))
+            .into_iter()
+            .map(|set| CutSet {
+                atoms: set.iter().map(|name| atoms[name].clone()).collect(),
+            })
+            .collect();
+        cut_sets.sort_by(|a, b| {
+            b.probability()
+                .total_cmp(&a.probability())
+                .then(a.atoms.len().cmp(&b.atoms.len()))
+        });
+        cut_sets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calculate::{AtomRegistry, Term};
+
+    #[test]
+    fn test_minimal_cut_sets() {
+        let mut registry = AtomRegistry::default();
+        let infra = Term::atom(registry.new_atom("infra".to_owned(), 0.99));
+        let replicas: Vec<Term> = (0..3)
+            .map(|index| Term::atom(registry.new_atom(format!("replica_{}", index), 0.9)))
+            .collect();
+
+        // the infra is a single point of failure, and any two of the three
+        // replicas take the quorum down
+        let term = Term::intersect(vec![
+            infra.clone(),
+            Term::at_least(2, replicas.clone()),
+            Term::union(vec![infra.clone(), infra]),
+        ]);
+
+        let cut_sets: Vec<Vec<String>> = term
+            .minimal_cut_sets(None)
+            .iter()
+            .map(|cut_set| {
+                cut_set
+                    .atoms()
+                    .iter()
+                    .map(|atom| atom.name().to_string())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            cut_sets,
+            vec![
+                vec!["infra"],
+                vec!["replica_0", "replica_1"],
+                vec!["replica_0", "replica_2"],
+                vec!["replica_1", "replica_2"],
+            ]
+        );
+
+        assert_eq!(term.minimal_cut_sets(Some(1)).len(), 1);
+
+        // a threshold larger than the number of terms is never satisfied, so
+        // it fails without any failure
+        let never = Term::at_least(4, replicas);
+        let cut_sets = never.minimal_cut_sets(None);
+        assert_eq!(cut_sets.len(), 1);
+        assert!(cut_sets[0].atoms().is_empty());
+        assert_eq!(never.calc(), 0.0);
+    }
+}
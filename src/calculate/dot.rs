@@ -13,173 +13,351 @@
 // limitations under the License.
 //
 
-use super::{MultiOp, Term, UnaryOp};
+use super::{Atom, Availability, MultiOp, Term, UnaryOp};
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-#[derive(Clone)]
+// RenderOptions controls how a term is drawn by `TermGraph`
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub graph_id: String,
+    // draw every atom once, rather than at every position in the tree
+    pub merge_atoms: bool,
+    // label every node with the probability of its sub-term, which is the sla
+    // for an atom
+    pub annotate: bool,
+    // fill every node by its contribution to the unavailability of the term,
+    // so the weakest branch stands out
+    pub heat_map: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            graph_id: "term".to_string(),
+            merge_atoms: false,
+            annotate: false,
+            heat_map: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct TermNode {
     content: String,
-    id: usize,
+    id: String,
+
+    // the probability of the sub-term, if the graph is annotated
+    probability: Option<f64>,
+    // the fraction of the term's unavailability which would be removed if
+    // the sub-term were always available, if the graph is heat-mapped
+    contribution: Option<f64>,
+}
+
+impl TermNode {
+    /// Get a reference to the node's id.
+    pub fn id(&self) -> &str {
+        self.id.as_ref()
+    }
+
+    /// Get a reference to the node's content.
+    pub fn content(&self) -> &str {
+        self.content.as_ref()
+    }
+
+    /// Get the node's probability.
+    pub fn probability(&self) -> Option<f64> {
+        self.probability
+    }
+
+    /// Get the node's contribution.
+    pub fn contribution(&self) -> Option<f64> {
+        self.contribution
+    }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct TermEdge {
     source: TermNode,
     target: TermNode,
 }
 
-impl<'a> dot::Labeller<'a, TermNode, TermEdge> for Term {
-    fn graph_id(&'a self) -> dot::Id<'a> {
-        dot::Id::new("example1").unwrap()
+impl TermEdge {
+    /// Get a reference to the edge's source.
+    pub fn source(&self) -> &TermNode {
+        &self.source
     }
 
-    fn node_id(&'a self, n: &TermNode) -> dot::Id<'a> {
-        dot::Id::new(format!("n{}", n.id)).unwrap()
+    /// Get a reference to the edge's target.
+    pub fn target(&self) -> &TermNode {
+        &self.target
     }
+}
 
-    fn node_label(&'a self, n: &TermNode) -> dot::LabelText<'a> {
-        dot::LabelText::LabelStr(Cow::Owned(n.content.clone()))
-    }
+// TermGraph is the logic tree of a term. The id of a node is derived from its
+// position in the tree, e.g. `n_1_0` is the first child of the second child of
+// the root, so the rendered graph is the same in every run. If the atoms are
+// merged, every atom is drawn once with the id `a<i>`, where i is the index in
+// `Term::atoms`.
+pub struct TermGraph {
+    nodes: Vec<TermNode>,
+    edges: Vec<TermEdge>,
+    options: RenderOptions,
+
+    // the ids of the merged atoms by their names
+    atoms: HashMap<String, String>,
+    seen: HashSet<String>,
+
+    // the unavailability of the whole term, and the contributions of the
+    // atoms, if the graph is heat-mapped
+    unavailability: f64,
+    contributions: HashMap<String, f64>,
 }
 
-impl Term {
-    fn node(&self) -> TermNode {
-        let id = self as *const Term as usize;
-        match self {
-            Term::None => TermNode {
-                content: "None".to_string(),
-                id,
-            },
-            Term::Unary {
-                atom,
-                op: UnaryOp::None,
-            } => TermNode {
-                content: atom.name().to_string(),
-                id,
-            },
-            Term::Unary {
-                atom: _,
-                op: UnaryOp::Not,
-            } => TermNode {
-                content: "Not".to_string(),
-                id,
-            },
-            Term::Multiple {
-                terms: _,
-                op: MultiOp::Union,
-            } => TermNode {
-                content: "Union".to_string(),
-                id,
-            },
-            Term::Multiple {
-                terms: _,
-                op: MultiOp::Intersect,
-            } => TermNode {
-                content: "Intersect".to_string(),
-                id,
+impl TermGraph {
+    pub fn new(term: &Term) -> TermGraph {
+        TermGraph::with_options(term, RenderOptions::default())
+    }
+
+    pub fn with_merged_atoms(term: &Term) -> TermGraph {
+        TermGraph::with_options(
+            term,
+            RenderOptions {
+                merge_atoms: true,
+                ..Default::default()
             },
+        )
+    }
+
+    pub fn with_options(term: &Term, options: RenderOptions) -> TermGraph {
+        let atoms = match options.merge_atoms {
+            true => term
+                .atoms()
+                .iter()
+                .enumerate()
+                .map(|(index, atom)| (atom.name().to_string(), format!("a{}", index)))
+                .collect(),
+            false => HashMap::new(),
+        };
+
+        let (unavailability, contributions) = match options.heat_map && !term.is_none() {
+            true => (
+                term.unavailability(),
+                term.importance()
+                    .iter()
+                    .map(|item| (item.atom().name().to_string(), item.contribution()))
+                    .collect(),
+            ),
+            false => (0.0, HashMap::new()),
+        };
+
+        let mut graph = TermGraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            options,
+            atoms,
+            seen: HashSet::new(),
+            unavailability,
+            contributions,
+        };
+        graph.visit(term, term, &mut Vec::new(), "n".to_string());
+        graph
+    }
+
+    /// Get a reference to the nodes.
+    pub fn nodes(&self) -> &[TermNode] {
+        self.nodes.as_ref()
+    }
+
+    /// Get a reference to the edges.
+    pub fn edges(&self) -> &[TermEdge] {
+        self.edges.as_ref()
+    }
+
+    // push adds the node, unless it's a merged atom which has been added
+    fn push(&mut self, node: TermNode) -> TermNode {
+        if self.seen.insert(node.id.clone()) {
+            self.nodes.push(node.clone());
         }
+        node
     }
-}
 
-impl<'a> dot::GraphWalk<'a, TermNode, TermEdge> for Term {
-    fn nodes(&self) -> dot::Nodes<'a, TermNode> {
-        // (assumes that |N| \approxeq |E|)
-        let mut nodes = Vec::new();
+    fn connect(&mut self, source: &TermNode, target: TermNode) {
+        self.edges.push(TermEdge {
+            source: source.clone(),
+            target,
+        });
+    }
 
-        match self {
-            Term::None => {
-                nodes.push(self.node());
-            }
-            Term::Unary {
-                atom: _,
-                op: UnaryOp::None,
-            } => {
-                nodes.push(self.node());
-            }
-            Term::Unary {
-                atom,
-                op: UnaryOp::Not,
-            } => {
-                nodes.push(self.node());
-                nodes.push(Term::atom(atom.clone()).node());
-            }
-            Term::Multiple {
-                terms,
-                op: MultiOp::Union,
-            } => {
-                nodes.push(self.node());
-                for union in terms.iter().map(|item| item.nodes().into_owned()) {
-                    nodes.extend(union);
-                }
-            }
-            Term::Multiple {
-                terms,
-                op: MultiOp::Intersect,
-            } => {
-                nodes.push(self.node());
-                for intersect in terms.iter().map(|item| item.nodes().into_owned()) {
-                    nodes.extend(intersect);
-                }
-            }
+    // fraction returns the contribution as a fraction of the unavailability
+    // of the whole term
+    fn fraction(&self, contribution: f64) -> Option<f64> {
+        match self.options.heat_map && self.unavailability > 0.0 {
+            true => Some((contribution / self.unavailability).clamp(0.0, 1.0)),
+            false => None,
         }
+    }
 
-        Cow::Owned(nodes)
+    fn atom(&mut self, atom: &Atom, id: String) -> TermNode {
+        let id = self.atoms.get(atom.name()).cloned().unwrap_or(id);
+        let contribution = self.contributions.get(atom.name()).copied();
+        self.push(TermNode {
+            content: atom.name().to_string(),
+            id,
+            probability: self.options.annotate.then(|| atom.probability()),
+            contribution: contribution.and_then(|contribution| self.fraction(contribution)),
+        })
     }
 
-    fn edges(&'a self) -> dot::Edges<'a, TermEdge> {
-        let mut edges = Vec::new();
+    // visit adds the sub-term at the `path` of the root and its children, and
+    // returns the node of the sub-term
+    fn visit(&mut self, root: &Term, term: &Term, path: &mut Vec<usize>, id: String) -> TermNode {
+        if let Term::Unary {
+            atom,
+            op: UnaryOp::None,
+        } = term
+        {
+            return self.atom(atom, id);
+        }
 
-        match self {
-            Term::None => {}
-            Term::Unary {
-                atom: _,
-                op: UnaryOp::None,
-            } => {}
-            Term::Unary {
-                atom,
-                op: UnaryOp::Not,
-            } => {
-                let from = self.node();
-                let to = Term::atom(atom.clone()).node();
-
-                edges.push(TermEdge {
-                    source: from,
-                    target: to,
-                });
+        let probability = match self.options.annotate && !term.is_none() {
+            true => Some(term.calc()),
+            false => None,
+        };
+        // the contribution of a sub-term is the unavailability removed by
+        // replacing it with an atom which is always available
+        let contribution = match self.options.heat_map && self.unavailability > 0.0 {
+            true => {
+                let up = Term::atom(Arc::new(Atom::new(format!("\0{}", id), 1.0)));
+                let forced = replace(root, path, up);
+                self.fraction(self.unavailability - forced.unavailability())
             }
+            false => None,
+        };
+
+        let (content, children) = match term {
+            Term::None => ("None".to_string(), &[][..]),
+            Term::Unary { .. } => ("Not".to_string(), &[][..]),
             Term::Multiple {
                 terms,
                 op: MultiOp::Union,
-            } => {
-                let from = self.node();
-
-                for union in terms.iter() {
-                    edges.push(TermEdge {
-                        source: from.clone(),
-                        target: union.node(),
-                    });
-                    edges.extend(union.edges().into_owned());
-                }
-            }
+            } => ("Union".to_string(), &terms[..]),
             Term::Multiple {
                 terms,
                 op: MultiOp::Intersect,
-            } => {
-                let from = self.node();
-
-                for intersect in terms.iter() {
-                    edges.push(TermEdge {
-                        source: from.clone(),
-                        target: intersect.node().clone(),
-                    });
-                    edges.extend(intersect.edges().into_owned());
-                }
-            }
+            } => ("Intersect".to_string(), &terms[..]),
+            Term::Threshold { terms, k } => (format!("AtLeast {}", k), &terms[..]),
+        };
+
+        let node = self.push(TermNode {
+            content,
+            id: id.clone(),
+            probability,
+            contribution,
+        });
+        if let Term::Unary { atom, .. } = term {
+            let child = self.atom(atom, format!("{}_0", id));
+            self.connect(&node, child);
         }
+        for (index, child) in children.iter().enumerate() {
+            path.push(index);
+            let child = self.visit(root, child, path, format!("{}_{}", id, index));
+            path.pop();
+            self.connect(&node, child);
+        }
+        node
+    }
+}
+
+// replace returns the term with the sub-term at the path replaced
+fn replace(term: &Term, path: &[usize], with: Term) -> Term {
+    let (index, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return with,
+    };
+
+    match term {
+        Term::Multiple { terms, op } => {
+            let mut terms = terms.clone();
+            terms[*index] = replace(&terms[*index], rest, with);
+            Term::Multiple { terms, op: *op }
+        }
+        Term::Threshold { terms, k } => {
+            let mut terms = terms.clone();
+            terms[*index] = replace(&terms[*index], rest, with);
+            Term::Threshold { terms, k: *k }
+        }
+        _ => unreachable!(),
+    }
+}
+
+// heat interpolates the color from white to red by the contribution
+fn heat(contribution: f64) -> String {
+    let level = (255.0 * (1.0 - contribution)).round() as u8;
+    format!("#ff{:02x}{:02x}", level, level)
+}
+
+// graph_id keeps the characters allowed in a dot id, and replaces the others
+// with underscores
+fn graph_id(id: &str) -> String {
+    let id: String = id
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    match id.chars().next() {
+        Some(c) if !c.is_ascii_digit() => id,
+        _ => format!("_{}", id),
+    }
+}
+
+impl<'a> dot::Labeller<'a, TermNode, TermEdge> for TermGraph {
+    fn graph_id(&'a self) -> dot::Id<'a> {
+        dot::Id::new(graph_id(&self.options.graph_id)).unwrap()
+    }
 
-        Cow::Owned(edges)
+    fn node_id(&'a self, n: &TermNode) -> dot::Id<'a> {
+        dot::Id::new(n.id.clone()).unwrap()
+    }
+
+    fn node_label(&'a self, n: &TermNode) -> dot::LabelText<'a> {
+        let label = match n.probability {
+            Some(probability) => format!(
+                "{}\n{}",
+                n.content,
+                Availability::from_probability(probability).percent()
+            ),
+            None => n.content.clone(),
+        };
+        dot::LabelText::LabelStr(Cow::Owned(label))
+    }
+
+    fn node_style(&'a self, n: &TermNode) -> dot::Style {
+        match n.contribution {
+            Some(_) => dot::Style::Filled,
+            None => dot::Style::None,
+        }
+    }
+
+    fn node_color(&'a self, n: &TermNode) -> Option<dot::LabelText<'a>> {
+        n.contribution
+            .map(|contribution| dot::LabelText::LabelStr(Cow::Owned(heat(contribution))))
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, TermNode, TermEdge> for TermGraph {
+    fn nodes(&'a self) -> dot::Nodes<'a, TermNode> {
+        Cow::Borrowed(&self.nodes)
+    }
+
+    fn edges(&'a self) -> dot::Edges<'a, TermEdge> {
+        Cow::Borrowed(&self.edges)
     }
 
     fn source(&self, e: &TermEdge) -> TermNode {
@@ -190,3 +368,121 @@ impl<'a> dot::GraphWalk<'a, TermNode, TermEdge> for Term {
         e.target.clone()
     }
 }
+
+// a term is rendered as its logic tree without merging the atoms, use
+// `TermGraph::with_merged_atoms` to draw every atom once
+impl<'a> dot::Labeller<'a, TermNode, TermEdge> for Term {
+    fn graph_id(&'a self) -> dot::Id<'a> {
+        dot::Id::new("term").unwrap()
+    }
+
+    fn node_id(&'a self, n: &TermNode) -> dot::Id<'a> {
+        dot::Id::new(n.id.clone()).unwrap()
+    }
+
+    fn node_label(&'a self, n: &TermNode) -> dot::LabelText<'a> {
+        dot::LabelText::LabelStr(Cow::Owned(n.content.clone()))
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, TermNode, TermEdge> for Term {
+    fn nodes(&self) -> dot::Nodes<'a, TermNode> {
+        Cow::Owned(TermGraph::new(self).nodes)
+    }
+
+    fn edges(&'a self) -> dot::Edges<'a, TermEdge> {
+        Cow::Owned(TermGraph::new(self).edges)
+    }
+
+    fn source(&self, e: &TermEdge) -> TermNode {
+        e.source.clone()
+    }
+
+    fn target(&self, e: &TermEdge) -> TermNode {
+        e.target.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use float_cmp::approx_eq;
+
+    use crate::calculate::{AtomRegistry, RenderOptions, Term, UnaryOp};
+
+    use super::TermGraph;
+
+    fn render<G>(graph: &G) -> String
+    where
+        for<'a> G: dot::Labeller<'a, super::TermNode, super::TermEdge>
+            + dot::GraphWalk<'a, super::TermNode, super::TermEdge>,
+    {
+        let mut output = Vec::new();
+        dot::render(graph, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_node_id() {
+        let mut registry = AtomRegistry::default();
+        let a = registry.new_atom("a".to_owned(), 0.9);
+        let b = registry.new_atom("b".to_owned(), 0.9);
+        let term = Term::union(vec![
+            Term::intersect(vec![Term::atom(a.clone()), Term::atom(b)]),
+            Term::Unary {
+                atom: Arc::clone(&a),
+                op: UnaryOp::Not,
+            },
+        ]);
+
+        // the ids are the positions in the tree, and don't change between
+        // renderings of equal terms
+        let output = render(&term);
+        assert_eq!(output, render(&term.clone()));
+        assert!(output.contains("n_0_1[label=\"b\"]"));
+        assert!(output.contains("n_1 -> n_1_0"));
+
+        // the atom a is drawn once, and the negation points to it
+        let graph = TermGraph::with_merged_atoms(&term);
+        assert_eq!(graph.nodes().len(), 5);
+        let output = render(&graph);
+        assert!(output.contains("n_0 -> a0"));
+        assert!(output.contains("n_1 -> a0"));
+    }
+
+    #[test]
+    fn test_render_options() {
+        let mut registry = AtomRegistry::default();
+        let infra = registry.new_atom("infra".to_owned(), 0.999);
+        let replicas: Vec<Term> = (0..3)
+            .map(|index| Term::atom(registry.new_atom(format!("replica_{}", index), 0.9)))
+            .collect();
+        let term = Term::intersect(vec![Term::atom(infra), Term::at_least(2, replicas)]);
+
+        let options = RenderOptions {
+            graph_id: "my service".to_owned(),
+            annotate: true,
+            heat_map: true,
+            ..Default::default()
+        };
+        let graph = TermGraph::with_options(&term, options);
+        let nodes = graph.nodes();
+        assert!(approx_eq!(
+            f64,
+            nodes[0].probability().unwrap(),
+            term.calc(),
+            epsilon = 0.0000001f64
+        ));
+        assert_eq!(nodes[1].probability(), Some(0.999));
+
+        // the whole term contributes all of its unavailability, and the
+        // replicas contribute more than the infra
+        assert_eq!(nodes[0].contribution(), Some(1.0));
+        assert!(nodes[2].contribution().unwrap() > nodes[1].contribution().unwrap());
+
+        let output = render(&graph);
+        assert!(output.starts_with("digraph my_service {"));
+        assert!(output.contains("n_1[label=\"AtLeast 2\\n97.20%\"][style=\"filled\"]"));
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{collections::HashMap, time::Duration};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{Atom, Term, MONTH};
+
+// OutageModel describes a component which fails and recovers repeatedly. The
+// up and down times are exponentially distributed with the mean time between
+// failures and the mean time to repair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutageModel {
+    mtbf: Duration,
+    mttr: Duration,
+}
+
+impl OutageModel {
+    pub fn new(mtbf: Duration, mttr: Duration) -> OutageModel {
+        OutageModel { mtbf, mttr }
+    }
+
+    // from_probability derives the outage model of a component with the
+    // probability, whose outages last `mttr` in average
+    pub fn from_probability(probability: f64, mttr: Duration) -> OutageModel {
+        OutageModel {
+            mtbf: mttr.mul_f64(probability / (1.0 - probability)),
+            mttr,
+        }
+    }
+
+    /// Get the mean time between failures.
+    pub fn mtbf(&self) -> Duration {
+        self.mtbf
+    }
+
+    /// Get the mean time to repair.
+    pub fn mttr(&self) -> Duration {
+        self.mttr
+    }
+
+    // probability returns the steady-state availability
+    pub fn probability(&self) -> f64 {
+        self.mtbf.as_secs_f64() / (self.mtbf.as_secs_f64() + self.mttr.as_secs_f64())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DowntimeOptions {
+    // the billing period to simulate
+    pub period: Duration,
+
+    // the mean duration of outages, for the atoms without an outage model,
+    // which should be positive
+    pub mean_outage: Duration,
+
+    // count the downtime in slots of this length, and a slot is down if the
+    // service is down at any time in it, e.g. a per-minute SLA. The length
+    // should be positive.
+    pub granularity: Option<Duration>,
+}
+
+impl Default for DowntimeOptions {
+    fn default() -> Self {
+        DowntimeOptions {
+            period: MONTH,
+            mean_outage: Duration::from_secs(60 * 60),
+            granularity: None,
+        }
+    }
+}
+
+// Downtime is the distribution of the total downtime of a service in a
+// period, with one sample for every simulated period. Without any sample, the
+// statistics are all zero.
+#[derive(Debug, Clone)]
+pub struct Downtime {
+    period: Duration,
+    samples: Vec<Duration>,
+}
+
+impl Downtime {
+    /// Get the period.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Get a reference to the samples, in the ascending order.
+    pub fn samples(&self) -> &[Duration] {
+        self.samples.as_ref()
+    }
+
+    pub fn mean(&self) -> Duration {
+        let total: f64 = self.samples.iter().map(Duration::as_secs_f64).sum();
+        Duration::from_secs_f64(total / self.samples.len().max(1) as f64)
+    }
+
+    // percentile returns the downtime which is not exceeded in `percentile`
+    // (in [0, 100]) of the periods, with the nearest rank method
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * self.samples.len() as f64).ceil() as usize;
+        self.samples[rank.clamp(1, self.samples.len()) - 1]
+    }
+
+    // breach_probability returns the fraction of periods whose availability
+    // is lower than the sla
+    pub fn breach_probability(&self, sla: f64) -> f64 {
+        let allowed = self.period.mul_f64(1.0 - sla);
+        let breaches = self
+            .samples
+            .iter()
+            .filter(|downtime| **downtime > allowed)
+            .count();
+        breaches as f64 / self.samples.len().max(1) as f64
+    }
+}
+
+// exponential draws an exponentially distributed number with the mean
+fn exponential<R: Rng + ?Sized>(mean: f64, rng: &mut R) -> f64 {
+    -mean * (1.0 - rng.gen::<f64>()).ln()
+}
+
+// delay draws the time until the next change of the atom. The durations are
+// exponentially distributed, so it only depends on the current state and the
+// probability of the atom, which may change with the state of its parents.
+fn delay<R: Rng + ?Sized>(
+    atom: &Atom,
+    up: bool,
+    probability: f64,
+    options: &DowntimeOptions,
+    rng: &mut R,
+) -> f64 {
+    let mttr = atom
+        .outage()
+        .map_or(options.mean_outage, |outage| outage.mttr())
+        .as_secs_f64();
+    if !up {
+        return match probability <= 0.0 {
+            true => f64::INFINITY,
+            false => exponential(mttr, rng),
+        };
+    }
+    if probability >= 1.0 {
+        return f64::INFINITY;
+    }
+
+    let mtbf = match (atom.outage(), atom.condition()) {
+        (Some(outage), None) => outage.mtbf().as_secs_f64(),
+        _ => mttr * probability / (1.0 - probability),
+    };
+    exponential(mtbf, rng)
+}
+
+impl Term {
+    // simulate_downtime simulates the atoms over the period for `trials`
+    // times, and returns the distribution of the downtime of the term.
+    //
+    // The period starts at a random time, so the initial state of an atom
+    // follows the steady-state distribution, and the parents are drawn before
+    // the atoms depending on them. Whenever a parent changes, the atoms
+    // depending on it are drawn again with the conditional probability, so
+    // every moment follows the same distribution as `calc`.
+    pub fn simulate_downtime<R: Rng + ?Sized>(
+        &self,
+        trials: usize,
+        options: &DowntimeOptions,
+        rng: &mut R,
+    ) -> Downtime {
+        let atoms = self.network();
+        let index: HashMap<&str, usize> = atoms
+            .iter()
+            .enumerate()
+            .map(|(index, atom)| (atom.name(), index))
+            .collect();
+        let probability = |position: usize, state: &[bool]| match atoms[position].condition() {
+            Some(condition) => condition.probability(|name| state[index[name]]),
+            None => atoms[position].probability(),
+        };
+        let period = options.period.as_secs_f64();
+
+        let mut samples = Vec::with_capacity(trials);
+        let mut state = vec![false; atoms.len()];
+        let mut next = vec![f64::INFINITY; atoms.len()];
+        let mut changed = vec![false; atoms.len()];
+        for _ in 0..trials {
+            for (position, atom) in atoms.iter().enumerate() {
+                let probability = probability(position, &state);
+                state[position] = rng.gen_bool(probability);
+                next[position] = delay(atom, state[position], probability, options, rng);
+            }
+
+            // sweep through the changes, and collect the intervals in which
+            // the term is not satisfied
+            let mut outages = Vec::new();
+            let mut down_since = None;
+            let mut time = 0.0;
+            loop {
+                let up = self.evaluate(&|atom: &Atom| state[index[atom.name()]]);
+                match (up, down_since) {
+                    (false, None) => down_since = Some(time),
+                    (true, Some(since)) => {
+                        outages.push((since, time));
+                        down_since = None;
+                    }
+                    _ => {}
+                }
+
+                let (first, at) = match next
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                {
+                    Some(change) => change,
+                    None => break,
+                };
+                if at >= period {
+                    break;
+                }
+                time = at;
+
+                // the atoms are placed after their parents, so the changes
+                // are propagated in one pass
+                changed.fill(false);
+                for (position, atom) in atoms.iter().enumerate().skip(first) {
+                    let redraw = position > first
+                        && atom.condition().is_some_and(|condition| {
+                            condition
+                                .parents()
+                                .iter()
+                                .any(|parent| changed[index[parent.name()]])
+                        });
+                    if position > first && !redraw {
+                        continue;
+                    }
+
+                    let probability = probability(position, &state);
+                    let previous = state[position];
+                    state[position] = match redraw {
+                        true => rng.gen_bool(probability),
+                        false => !previous,
+                    };
+                    next[position] = time + delay(atom, state[position], probability, options, rng);
+                    changed[position] = state[position] != previous;
+                }
+            }
+            if let Some(since) = down_since {
+                outages.push((since, period));
+            }
+
+            samples.push(Duration::from_secs_f64(downtime(&outages, options)));
+        }
+        samples.sort();
+
+        Downtime {
+            period: options.period,
+            samples,
+        }
+    }
+
+    pub fn simulate_downtime_with_seed(
+        &self,
+        trials: usize,
+        options: &DowntimeOptions,
+        seed: u64,
+    ) -> Downtime {
+        self.simulate_downtime(trials, options, &mut StdRng::seed_from_u64(seed))
+    }
+}
+
+// downtime returns the total length of the sorted outages in seconds, or the
+// total length of the slots touched by them
+fn downtime(outages: &[(f64, f64)], options: &DowntimeOptions) -> f64 {
+    let slot = match options.granularity {
+        Some(granularity) => granularity.as_secs_f64(),
+        None => return outages.iter().map(|(start, end)| end - start).sum(),
+    };
+
+    let period = options.period.as_secs_f64();
+    let mut slots = 0.0;
+    // the end of the last counted slot
+    let mut counted = 0.0;
+    for (start, end) in outages {
+        let first = (start / slot).floor() * slot;
+        let last = ((end / slot).ceil() * slot).min(period);
+        let first = f64::max(first, counted);
+        if last > first {
+            slots += last - first;
+            counted = last;
+        }
+    }
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use float_cmp::approx_eq;
+
+    use crate::calculate::{AtomRegistry, DowntimeOptions, OutageModel, Term};
+
+    #[test]
+    fn test_simulate_downtime() {
+        let mut registry = AtomRegistry::default();
+        let hour = Duration::from_secs(3600);
+        let database =
+            registry.new_atom_with_outage("database".to_owned(), OutageModel::new(hour * 99, hour));
+        let network = registry.new_atom("network".to_owned(), 0.999);
+        let term = Term::intersect(vec![Term::atom(database), Term::atom(network)]);
+
+        let options = DowntimeOptions::default();
+        let downtime = term.simulate_downtime_with_seed(20_000, &options, 0);
+
+        // the expected downtime is the unavailability of the period
+        let expected = options.period.mul_f64(term.unavailability());
+        assert!(approx_eq!(
+            f64,
+            downtime.mean().as_secs_f64() / expected.as_secs_f64(),
+            1.0,
+            epsilon = 0.02
+        ));
+        assert!(downtime.percentile(50.0) <= downtime.percentile(99.0));
+        assert!(downtime.breach_probability(0.0) == 0.0);
+        assert!(downtime.breach_probability(1.0) > 0.9);
+
+        // every outage is rounded up to whole minutes
+        let options = DowntimeOptions {
+            granularity: Some(Duration::from_secs(60)),
+            ..options
+        };
+        let rounded = term.simulate_downtime_with_seed(20_000, &options, 0);
+        assert!(rounded.mean() >= downtime.mean());
+        assert!(rounded
+            .samples()
+            .iter()
+            .all(|sample| sample.as_secs() % 60 == 0));
+
+        // none is always available, and doesn't change the downtime
+        let with_none = Term::intersect(vec![Term::None, term.clone()]);
+        assert_eq!(
+            with_none
+                .simulate_downtime_with_seed(1000, &options, 0)
+                .samples(),
+            term.simulate_downtime_with_seed(1000, &options, 0)
+                .samples()
+        );
+        let none = Term::None.simulate_downtime_with_seed(1000, &options, 0);
+        assert_eq!(none.mean(), Duration::ZERO);
+
+        let empty = term.simulate_downtime_with_seed(0, &options, 0);
+        assert_eq!(empty.mean(), Duration::ZERO);
+        assert_eq!(empty.percentile(99.0), Duration::ZERO);
+        assert_eq!(empty.breach_probability(1.0), 0.0);
+    }
+
+    #[test]
+    fn test_simulate_conditional_downtime() {
+        let mut registry = AtomRegistry::default();
+        let database = registry.new_atom("database".to_owned(), 0.99);
+        // the cache is more likely down when the database is down
+        let cache = registry.new_conditional_atom(
+            "cache".to_owned(),
+            vec![database.clone()],
+            vec![0.5, 0.999],
+        );
+        let term = Term::intersect(vec![Term::atom(database), Term::atom(cache)]);
+
+        let options = DowntimeOptions::default();
+        let downtime = term.simulate_downtime_with_seed(20_000, &options, 0);
+        let expected = options.period.mul_f64(1.0 - term.calc());
+        assert!(approx_eq!(
+            f64,
+            downtime.mean().as_secs_f64() / expected.as_secs_f64(),
+            1.0,
+            epsilon = 0.02
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+
+use num_bigint::BigInt;
+use num_traits::{One, Pow, Signed, ToPrimitive, Zero};
+
+pub use num_rational::BigRational;
+
+use crate::error::SlacError;
+
+use super::{Atom, AtomRegistry, Bdd, Term};
+
+// parse_decimal parses a decimal number like "0.9995" or a percentage like
+// "99.95%" into an exact rational number
+pub fn parse_decimal(text: &str) -> Option<BigRational> {
+    let text = text.trim();
+    let (text, scale) = match text.strip_suffix('%') {
+        Some(text) => (text.trim_end(), 2),
+        None => (text, 0),
+    };
+
+    let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty()) || !is_digits(integer) || !is_digits(fraction) {
+        return None;
+    }
+
+    let numer: BigInt = format!("{}{}", integer, fraction).parse().ok()?;
+    let denom = Pow::pow(BigInt::from(10u32), fraction.len() + scale);
+    Some(BigRational::new(numer, denom))
+}
+
+// to_decimal formats the rational number as a decimal, rounded to the given
+// number of fractional digits
+pub fn to_decimal(value: &BigRational, digits: usize) -> String {
+    let scale = Pow::pow(BigInt::from(10u32), digits);
+    let scaled = (value * BigRational::from_integer(scale))
+        .round()
+        .to_integer();
+
+    let sign = if scaled.is_negative() { "-" } else { "" };
+    let mut text = scaled.abs().to_string();
+    if text.len() <= digits {
+        text = format!("{}{}", "0".repeat(digits + 1 - text.len()), text);
+    }
+
+    let (integer, fraction) = text.split_at(text.len() - digits);
+    if fraction.is_empty() {
+        format!("{}{}", sign, integer)
+    } else {
+        format!("{}{}.{}", sign, integer, fraction)
+    }
+}
+
+impl Atom {
+    // exact_probability returns the rational probability which the atom is
+    // registered with, or the exact value of its floating probability. A
+    // floating number is a binary fraction, e.g. 0.9995 is actually
+    // 0.99950000000000005507..., so a decimal sla should be registered by
+    // `AtomRegistry::new_exact_atom` to be calculated exactly.
+    pub fn exact_probability(&self) -> BigRational {
+        match &self.exact {
+            Some(probability) => probability.clone(),
+            None => exact_float(self.probability()),
+        }
+    }
+}
+
+fn exact_float(probability: f64) -> BigRational {
+    BigRational::from_float(probability).expect("the probability should be finite")
+}
+
+impl AtomRegistry {
+    // new_exact_atom returns the atom with the name, or registers a new one
+    // with the rational probability, e.g. from `parse_decimal`. The exact
+    // calculation uses the rational probability as is, and the others use the
+    // nearest floating number.
+    pub fn new_exact_atom(&mut self, name: String, probability: BigRational) -> Arc<Atom> {
+        self.try_new_exact_atom(name, probability)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new_exact_atom(
+        &mut self,
+        name: String,
+        probability: BigRational,
+    ) -> Result<Arc<Atom>, SlacError> {
+        let float = probability.to_f64().unwrap_or(f64::NAN);
+        if probability.is_negative() || probability > BigRational::one() {
+            return Err(SlacError::InvalidSla { name, sla: float });
+        }
+
+        let atom = self.registry.entry(name.clone()).or_insert_with(|| {
+            let mut atom = Atom::new(name, float);
+            atom.exact = Some(probability.clone());
+            Arc::new(atom)
+        });
+        if atom.exact_probability() != probability {
+            return Err(SlacError::ConflictingAtom {
+                name: atom.name().to_string(),
+                existing: atom.probability(),
+                requested: float,
+            });
+        }
+
+        Ok(atom.clone())
+    }
+}
+
+impl Bdd {
+    pub fn exact_probability(&self) -> BigRational {
+        self.fold(
+            BigRational::zero(),
+            BigRational::one(),
+            |atom, low, high| {
+                let probability = atom.exact_probability();
+                let unavailability = BigRational::one() - &probability;
+                probability * high + unavailability * low
+            },
+        )
+    }
+}
+
+impl Term {
+    // calc_exact calculates the probability with rational numbers, so the
+    // result is exact without any rounding error. The conditional atoms are
+    // conditioned on their parents like `calc`, with the exact values of the
+    // floating numbers in their tables.
+    pub fn calc_exact(&self) -> BigRational {
+        if self.is_conditional() {
+            return self.condition_with(exact_float, Atom::exact_probability, |term| {
+                term.calc_exact()
+            });
+        }
+
+        Bdd::compile(self).exact_probability()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use crate::calculate::{AtomRegistry, Term};
+
+    use super::{parse_decimal, to_decimal, BigRational};
+
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(BigInt::from(numer), BigInt::from(denom))
+    }
+
+    #[test]
+    fn test_decimal() {
+        assert_eq!(parse_decimal("99.95%"), Some(ratio(9995, 10000)));
+        assert_eq!(parse_decimal("0.9995"), Some(ratio(9995, 10000)));
+        assert_eq!(parse_decimal("1"), Some(ratio(1, 1)));
+        assert_eq!(parse_decimal(".5"), Some(ratio(1, 2)));
+        assert_eq!(parse_decimal("0.9.5"), None);
+        assert_eq!(parse_decimal("%"), None);
+
+        assert_eq!(to_decimal(&ratio(1, 3), 5), "0.33333");
+        assert_eq!(to_decimal(&ratio(2, 3), 5), "0.66667");
+        assert_eq!(to_decimal(&ratio(1, 40), 1), "0.0");
+        assert_eq!(to_decimal(&ratio(7, 2), 0), "4");
+    }
+
+    #[test]
+    fn test_calc_exact() {
+        let mut registry = AtomRegistry::default();
+        let decimal = |text| parse_decimal(text).unwrap();
+        let atom_a = registry.new_exact_atom("atom_a".to_owned(), decimal("99.95%"));
+        let atom_b = registry.new_exact_atom("atom_b".to_owned(), decimal("0.9999"));
+        assert_eq!(atom_a.probability(), 0.9995);
+
+        let union = Term::union(vec![Term::atom(atom_a), Term::atom(atom_b)]);
+        // 1 - 0.0005 * 0.0001
+        assert_eq!(union.calc_exact(), ratio(19_999_999, 20_000_000));
+        assert_eq!(to_decimal(&union.calc_exact(), 10), "0.9999999500");
+
+        // a floating probability is a binary fraction
+        let atom_c = registry.new_atom("atom_c".to_owned(), 0.9995);
+        assert_eq!(
+            to_decimal(&Term::atom(atom_c).calc_exact(), 20),
+            "0.99950000000000005507"
+        );
+
+        assert!(registry
+            .try_new_exact_atom("atom_a".to_owned(), decimal("0.9"))
+            .is_err());
+        assert!(registry
+            .try_new_exact_atom("atom_d".to_owned(), ratio(3, 2))
+            .is_err());
+    }
+
+    #[test]
+    fn test_calc_exact_conditional() {
+        let mut registry = AtomRegistry::default();
+        let database = registry.new_exact_atom("database".to_owned(), ratio(9, 10));
+        let cache = registry.new_conditional_atom(
+            "cache".to_owned(),
+            vec![database.clone()],
+            vec![0.5, 0.99],
+        );
+
+        // the cache is down with probability 0.5 when the database is down
+        let term = Term::union(vec![Term::atom(database), Term::atom(cache)]);
+        assert_eq!(term.calc_exact(), ratio(19, 20));
+    }
+}
@@ -0,0 +1,406 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fmt;
+
+use crate::error::ParseError;
+
+use super::{AtomRegistry, MultiOp, Term, UnaryOp};
+
+// the textual expression of a term is like `(a & b) | !c | atleast(2, x, y, z)`,
+// where `&` binds tighter than `|`, and `!` negates an atom or a parenthesized
+// expression. A name with other characters than letters, digits and `_-.:/`
+// is written in double quotes, e.g. `"ccf(a, b)"`.
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    Comma,
+    And,
+    Or,
+    Not,
+    Name(String),
+    Quoted(String),
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || "_-.:/".contains(c)
+}
+
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            '&' => Token::And,
+            '|' => Token::Or,
+            '!' => Token::Not,
+            '"' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => name.push(c),
+                            None => break,
+                        },
+                        Some((_, c)) => name.push(c),
+                        None => {
+                            return Err(ParseError::UnexpectedToken {
+                                position: input.len(),
+                                expected: "a closing quote",
+                            })
+                        }
+                    }
+                }
+                Token::Quoted(name)
+            }
+            c if is_name_char(c) => {
+                let mut name = c.to_string();
+                while let Some((_, c)) = chars.peek().filter(|(_, c)| is_name_char(*c)) {
+                    name.push(*c);
+                    chars.next();
+                }
+                Token::Name(name)
+            }
+            _ => {
+                return Err(ParseError::UnexpectedToken {
+                    position,
+                    expected: "an operator or a name",
+                })
+            }
+        };
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    end: usize,
+    registry: &'a AtomRegistry,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map(|(position, _)| *position)
+            .unwrap_or(self.end)
+    }
+
+    fn expect(&mut self, token: Token, expected: &'static str) -> Result<(), ParseError> {
+        if self.peek() != Some(&token) {
+            return Err(ParseError::UnexpectedToken {
+                position: self.position(),
+                expected,
+            });
+        }
+        self.next += 1;
+        Ok(())
+    }
+
+    // union := intersect ('|' intersect)*
+    fn union(&mut self) -> Result<Term, ParseError> {
+        let mut terms = vec![self.intersect()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next += 1;
+            terms.push(self.intersect()?);
+        }
+
+        Ok(match terms.len() {
+            1 => terms.pop().unwrap(),
+            _ => Term::union(terms),
+        })
+    }
+
+    // intersect := unary ('&' unary)*
+    fn intersect(&mut self) -> Result<Term, ParseError> {
+        let mut terms = vec![self.unary()?];
+        while self.peek() == Some(&Token::And) {
+            self.next += 1;
+            terms.push(self.unary()?);
+        }
+
+        Ok(match terms.len() {
+            1 => terms.pop().unwrap(),
+            _ => Term::intersect(terms),
+        })
+    }
+
+    // unary := '!' unary | primary
+    fn unary(&mut self) -> Result<Term, ParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.next += 1;
+            return Ok(negate(self.unary()?));
+        }
+
+        self.primary()
+    }
+
+    // primary := '(' union ')' | 'none' | 'atleast' '(' k (',' union)* ')' | name
+    fn primary(&mut self) -> Result<Term, ParseError> {
+        let position = self.position();
+        let token = self.peek().cloned();
+        match token {
+            Some(Token::LeftParen) => {
+                self.next += 1;
+                let term = self.union()?;
+                self.expect(Token::RightParen, "a closing parenthesis")?;
+                Ok(term)
+            }
+            Some(Token::Name(name)) if name == "none" => {
+                self.next += 1;
+                Ok(Term::None)
+            }
+            Some(Token::Name(name))
+                if name == "atleast"
+                    && matches!(self.tokens.get(self.next + 1), Some((_, Token::LeftParen))) =>
+            {
+                self.next += 2;
+                let k = match self.peek() {
+                    Some(Token::Name(k)) => k.parse::<usize>().ok(),
+                    _ => None,
+                }
+                .ok_or(ParseError::UnexpectedToken {
+                    position: self.position(),
+                    expected: "the threshold of atleast",
+                })?;
+                self.next += 1;
+
+                let mut terms = Vec::new();
+                while self.peek() == Some(&Token::Comma) {
+                    self.next += 1;
+                    terms.push(self.union()?);
+                }
+                self.expect(Token::RightParen, "a comma or a closing parenthesis")?;
+
+                if k > terms.len() {
+                    return Err(ParseError::InvalidThreshold {
+                        position,
+                        k,
+                        terms: terms.len(),
+                    });
+                }
+                Ok(Term::at_least(k, terms))
+            }
+            Some(Token::Name(name)) | Some(Token::Quoted(name)) => {
+                self.next += 1;
+                let atom = self
+                    .registry
+                    .get(&name)
+                    .ok_or(ParseError::UnknownAtom { position, name })?;
+                Ok(Term::atom(atom))
+            }
+            _ => Err(ParseError::UnexpectedToken {
+                position,
+                expected: "a name or an opening parenthesis",
+            }),
+        }
+    }
+}
+
+// negate pushes the negation down to the atoms by De Morgan's laws, as only an
+// atom could be negated in a term
+fn negate(term: Term) -> Term {
+    match term {
+        Term::None => Term::None,
+        Term::Unary { atom, op } => Term::Unary {
+            atom,
+            op: match op {
+                UnaryOp::None => UnaryOp::Not,
+                UnaryOp::Not => UnaryOp::None,
+            },
+        },
+        Term::Multiple { terms, op } => Term::Multiple {
+            terms: terms.into_iter().map(negate).collect(),
+            op: match op {
+                MultiOp::Union => MultiOp::Intersect,
+                MultiOp::Intersect => MultiOp::Union,
+            },
+        },
+        // none in a threshold is always satisfied, and less than k of the
+        // others are satisfied iff at least n - k + 1 of them are not
+        Term::Threshold { terms, k } => {
+            let always = terms.iter().filter(|term| term.is_none()).count();
+            let terms: Vec<Term> = terms
+                .into_iter()
+                .filter(|term| !term.is_none())
+                .map(negate)
+                .collect();
+            let k = (terms.len() + 1).saturating_sub(k.saturating_sub(always));
+            Term::at_least(k, terms)
+        }
+    }
+}
+
+impl Term {
+    // parse reads a term from its textual expression, and every name refers
+    // to an atom registered in the registry
+    pub fn parse(input: &str, registry: &AtomRegistry) -> Result<Term, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            next: 0,
+            end: input.len(),
+            registry,
+        };
+
+        let term = parser.union()?;
+        if parser.peek().is_some() {
+            return Err(ParseError::UnexpectedToken {
+                position: parser.position(),
+                expected: "an operator",
+            });
+        }
+
+        Ok(term)
+    }
+}
+
+fn write_name(f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+    let plain =
+        !name.is_empty() && name.chars().all(is_name_char) && name != "none" && name != "atleast";
+    if plain {
+        return write!(f, "{}", name);
+    }
+
+    write!(f, "\"")?;
+    for c in name.chars() {
+        if c == '"' || c == '\\' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    write!(f, "\"")
+}
+
+// Display prints the term in the syntax accepted by `Term::parse`
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::None => write!(f, "none"),
+            Term::Unary {
+                atom,
+                op: UnaryOp::None,
+            } => write_name(f, atom.name()),
+            Term::Unary {
+                atom,
+                op: UnaryOp::Not,
+            } => {
+                write!(f, "!")?;
+                write_name(f, atom.name())
+            }
+            Term::Multiple { terms, .. } if terms.is_empty() => write!(f, "none"),
+            Term::Multiple { terms, op } => {
+                let separator = match op {
+                    MultiOp::Union => " | ",
+                    MultiOp::Intersect => " & ",
+                };
+                for (index, term) in terms.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "{}", separator)?;
+                    }
+                    // the nested expressions are parenthesized to keep the
+                    // structure of the term
+                    match term {
+                        Term::Multiple { terms, .. } if terms.len() > 1 => write!(f, "({})", term)?,
+                        _ => write!(f, "{}", term)?,
+                    }
+                }
+                Ok(())
+            }
+            Term::Threshold { terms, k } => {
+                write!(f, "atleast({}", k)?;
+                for term in terms.iter() {
+                    write!(f, ", {}", term)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use crate::{
+        calculate::{AtomRegistry, Term},
+        error::ParseError,
+    };
+
+    #[test]
+    fn test_parse() {
+        let mut registry = AtomRegistry::default();
+        for name in ["a", "b", "c", "x", "y", "z", "ccf(a, b)"] {
+            registry.new_atom(name.to_owned(), 0.9);
+        }
+
+        let term = Term::parse("(a & b) | !c | atleast(2, x, y, z)", &registry).unwrap();
+        let expected = 1.0 - (1.0 - 0.81) * (1.0 - 0.1) * (1.0 - 0.972);
+        assert!(approx_eq!(
+            f64,
+            term.calc(),
+            expected,
+            epsilon = 0.0000001f64
+        ));
+        assert_eq!(term.to_string(), "(a & b) | !c | atleast(2, x, y, z)");
+
+        // negating an expression is pushed down to the atoms
+        let term = Term::parse("!(a | atleast(2, x, y, z)) & \"ccf(a, b)\"", &registry).unwrap();
+        assert!(approx_eq!(
+            f64,
+            term.calc(),
+            0.1 * (1.0 - 0.972) * 0.9,
+            epsilon = 0.0000001f64
+        ));
+        assert_eq!(
+            term.to_string(),
+            "(!a & atleast(2, !x, !y, !z)) & \"ccf(a, b)\""
+        );
+        let reparsed = Term::parse(&term.to_string(), &registry).unwrap();
+        assert_eq!(term.to_string(), reparsed.to_string());
+
+        assert_eq!(
+            Term::parse("a & d", &registry).unwrap_err(),
+            ParseError::UnknownAtom {
+                position: 4,
+                name: "d".to_owned()
+            }
+        );
+        assert_eq!(
+            Term::parse("atleast(4, x, y, z)", &registry).unwrap_err(),
+            ParseError::InvalidThreshold {
+                position: 0,
+                k: 4,
+                terms: 3
+            }
+        );
+        assert!(Term::parse("(a & b", &registry).is_err());
+        assert!(Term::parse("a b", &registry).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::sync::Arc;
+
+use super::{Atom, Term};
+
+// Importance describes how much an atom affects the availability of a term.
+// Everything is calculated in the unavailability space to keep the precision.
+#[derive(Debug, Clone)]
+pub struct Importance {
+    atom: Arc<Atom>,
+
+    // the unavailability of the term when the atom is always available
+    up: f64,
+    // the unavailability of the term when the atom is never available
+    down: f64,
+    // the unavailability of the term
+    unavailability: f64,
+}
+
+// ratio returns a / b, where 0 / 0 is considered as 1
+fn ratio(a: f64, b: f64) -> f64 {
+    if a == b {
+        1.0
+    } else {
+        a / b
+    }
+}
+
+impl Importance {
+    /// Get a reference to the atom.
+    pub fn atom(&self) -> &Arc<Atom> {
+        &self.atom
+    }
+
+    // birnbaum is the partial derivative of the probability of the term with
+    // respect to the probability of the atom
+    pub fn birnbaum(&self) -> f64 {
+        self.down - self.up
+    }
+
+    // fussell_vesely is the fraction of the unavailability which involves the
+    // failure of the atom
+    pub fn fussell_vesely(&self) -> f64 {
+        if self.unavailability == 0.0 {
+            0.0
+        } else {
+            (self.unavailability - self.up) / self.unavailability
+        }
+    }
+
+    // contribution is the unavailability which involves the failure of the
+    // atom, and would be removed if the atom were always available. The
+    // contributions of all atoms may sum up to more than the unavailability,
+    // as a failure could involve many atoms.
+    pub fn contribution(&self) -> f64 {
+        self.unavailability - self.up
+    }
+
+    // risk_achievement_worth is the factor by which the unavailability
+    // increases when the atom is never available
+    pub fn risk_achievement_worth(&self) -> f64 {
+        ratio(self.down, self.unavailability)
+    }
+
+    // risk_reduction_worth is the factor by which the unavailability decreases
+    // when the atom is always available
+    pub fn risk_reduction_worth(&self) -> f64 {
+        ratio(self.unavailability, self.up)
+    }
+}
+
+impl Term {
+    // importance calculates the importance measures of every atom, by forcing
+    // the atom up and down and calculating the term again. The result is sorted
+    // by the Birnbaum importance, from the most important one.
+    pub fn importance(&self) -> Vec<Importance> {
+        let unavailability = self.unavailability();
+
+        let mut importance: Vec<Importance> = self
+            .atoms()
+            .into_iter()
+            .map(|atom| Importance {
+                up: self.force(atom.name(), true).unavailability(),
+                down: self.force(atom.name(), false).unavailability(),
+                unavailability,
+                atom,
+            })
+            .collect();
+        importance.sort_by(|a, b| b.birnbaum().total_cmp(&a.birnbaum()));
+        importance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+
+    use crate::calculate::{AtomRegistry, Term};
+
+    #[test]
+    fn test_importance() {
+        let mut registry = AtomRegistry::default();
+        let infra = registry.new_atom("infra".to_owned(), 0.9);
+        let replica_a = registry.new_atom("replica_a".to_owned(), 0.8);
+        let replica_b = registry.new_atom("replica_b".to_owned(), 0.8);
+
+        // infra & (replica_a | replica_b)
+        let term = Term::intersect(vec![
+            Term::atom(infra),
+            Term::union(vec![Term::atom(replica_a), Term::atom(replica_b)]),
+        ]);
+        let probability = 0.9 * 0.96;
+
+        let importance = term.importance();
+        assert_eq!(importance[0].atom().name(), "infra");
+
+        let infra = &importance[0];
+        assert!(approx_eq!(
+            f64,
+            infra.birnbaum(),
+            0.96,
+            epsilon = 0.0000001f64
+        ));
+        assert!(approx_eq!(
+            f64,
+            infra.fussell_vesely(),
+            (0.96 - probability) / (1.0 - probability),
+            epsilon = 0.0000001f64
+        ));
+        assert!(approx_eq!(
+            f64,
+            infra.risk_achievement_worth(),
+            1.0 / (1.0 - probability),
+            epsilon = 0.0000001f64
+        ));
+        assert!(approx_eq!(
+            f64,
+            infra.risk_reduction_worth(),
+            (1.0 - probability) / 0.04,
+            epsilon = 0.0000001f64
+        ));
+
+        let replica = &importance[1];
+        assert!(approx_eq!(
+            f64,
+            replica.birnbaum(),
+            0.9 * 0.2,
+            epsilon = 0.0000001f64
+        ));
+    }
+}
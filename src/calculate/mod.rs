@@ -13,9 +13,37 @@
 // limitations under the License.
 //
 
+mod availability;
+mod bdd;
+mod budget;
+mod conditional;
+mod credit;
+mod cut_set;
 mod dot;
+mod downtime;
+#[cfg(feature = "exact")]
+mod exact;
+mod expression;
+mod importance;
+mod monte_carlo;
 mod optimize;
 mod probability;
+#[cfg(feature = "serde")]
+mod serialize;
 mod term;
+mod threshold;
 
+pub use self::dot::{RenderOptions, TermEdge, TermGraph, TermNode};
+pub use availability::*;
+pub use bdd::Bdd;
+pub use budget::ErrorBudget;
+pub use conditional::Condition;
+pub use credit::*;
+pub use cut_set::CutSet;
+pub use downtime::*;
+#[cfg(feature = "exact")]
+pub use exact::*;
+pub use importance::Importance;
+pub use monte_carlo::Estimate;
+pub use probability::Engine;
 pub use term::*;
@@ -0,0 +1,159 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{Atom, MultiOp, Term, UnaryOp};
+
+// the z-score of the 95% confidence level
+const Z_95: f64 = 1.959963984540054;
+
+// Estimate is the result of sampling a term
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    successes: usize,
+    samples: usize,
+}
+
+impl Estimate {
+    /// Get the number of samples in which the term is satisfied.
+    pub fn successes(&self) -> usize {
+        self.successes
+    }
+
+    /// Get the number of samples.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.successes as f64 / self.samples as f64
+    }
+
+    // confidence_interval returns the 95% Wilson score interval, which still
+    // works well when the probability is close to 0 or 1
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let n = self.samples as f64;
+        let p = self.probability();
+        let z2 = Z_95 * Z_95;
+
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+}
+
+impl Term {
+    // evaluate returns whether the term is satisfied, with the state of every
+    // atom given by `state`. None is always satisfied, and so is an empty union
+    // or intersect, the same as `calc`.
+    pub fn evaluate<F: Fn(&Atom) -> bool>(&self, state: &F) -> bool {
+        match self {
+            Term::None => true,
+            Term::Unary {
+                atom,
+                op: UnaryOp::None,
+            } => state(atom),
+            Term::Unary {
+                atom,
+                op: UnaryOp::Not,
+            } => !state(atom),
+            Term::Multiple {
+                terms,
+                op: MultiOp::Union,
+            } => terms.is_empty() || terms.iter().any(|term| term.evaluate(state)),
+            Term::Multiple {
+                terms,
+                op: MultiOp::Intersect,
+            } => terms.iter().all(|term| term.evaluate(state)),
+            Term::Threshold { terms, k } => {
+                terms.iter().filter(|term| term.evaluate(state)).count() >= *k
+            }
+        }
+    }
+
+    // estimate draws every atom as a Bernoulli variable with its probability,
+    // or the conditional probability given its parents, and counts the samples
+    // in which the term is satisfied. It panics if `samples` is zero, as
+    // nothing could be estimated without any sample.
+    pub fn estimate<R: Rng + ?Sized>(&self, samples: usize, rng: &mut R) -> Estimate {
+        assert!(samples > 0, "estimate requires at least one sample");
+
+        // the parents are drawn before the atoms depending on them
+        let atoms = self.network();
+        let index: HashMap<&str, usize> = atoms
+            .iter()
+            .enumerate()
+            .map(|(index, atom)| (atom.name(), index))
+            .collect();
+
+        let mut state = vec![false; atoms.len()];
+        let mut successes = 0;
+        for _ in 0..samples {
+            for (position, atom) in atoms.iter().enumerate() {
+                let probability = match atom.condition() {
+                    Some(condition) => condition.probability(|name| state[index[name]]),
+                    None => atom.probability(),
+                };
+                state[position] = rng.gen_bool(probability);
+            }
+
+            if self.evaluate(&|atom: &Atom| state[index[atom.name()]]) {
+                successes += 1;
+            }
+        }
+
+        Estimate { successes, samples }
+    }
+
+    pub fn estimate_with_seed(&self, samples: usize, seed: u64) -> Estimate {
+        self.estimate(samples, &mut StdRng::seed_from_u64(seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::calculate::{AtomRegistry, Term};
+
+    #[test]
+    fn test_estimate() {
+        let mut registry = AtomRegistry::default();
+        let members: Vec<Term> = (0..5)
+            .map(|index| Term::atom(registry.new_atom(format!("atom_{}", index), 0.9)))
+            .collect();
+        let connection = Term::atom(registry.new_atom("connection".to_owned(), 0.95));
+        let term = Term::intersect(vec![connection, Term::at_least(3, members)]);
+
+        let estimate = term.estimate_with_seed(100000, 42);
+        assert_eq!(estimate, term.estimate_with_seed(100000, 42));
+
+        let (lower, upper) = estimate.confidence_interval();
+        let expected = term.calc();
+        assert!(lower <= expected && expected <= upper);
+
+        // none is always satisfied
+        assert_eq!(Term::None.estimate_with_seed(100, 42).probability(), 1.0);
+        let union = Term::union(vec![Term::None]);
+        assert_eq!(union.estimate_with_seed(100, 42).probability(), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one sample")]
+    fn test_estimate_without_samples() {
+        Term::None.estimate_with_seed(0, 42);
+    }
+}
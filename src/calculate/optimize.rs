@@ -13,7 +13,11 @@
 // limitations under the License.
 //
 
-use super::{MultiOp, Term, UnaryOp};
+use std::sync::Arc;
+
+use super::{Atom, MultiOp, Term, UnaryOp};
+
+use itertools::Itertools;
 
 impl Term {
     pub fn remove_none(self) -> Option<Term> {
@@ -21,11 +25,16 @@ impl Term {
             Term::None => None,
             Term::Unary { atom, op } => Some(Term::Unary { atom, op }),
             Term::Multiple { terms, op } => {
+                // none is always satisfied, so it satisfies a union, and is
+                // ignored by an intersect
+                let total = terms.len();
                 let non_empty_terms: Vec<Term> = terms
                     .into_iter()
                     .filter_map(|term| term.remove_none())
                     .collect();
-                if non_empty_terms.is_empty() {
+                if non_empty_terms.is_empty()
+                    || (op == MultiOp::Union && non_empty_terms.len() < total)
+                {
                     None
                 } else {
                     Some(Term::Multiple {
@@ -34,6 +43,24 @@ impl Term {
                     })
                 }
             }
+            Term::Threshold { terms, k } => {
+                // none is always satisfied in a threshold, so it reduces the
+                // number of required terms
+                let total = terms.len();
+                let non_empty_terms: Vec<Term> = terms
+                    .into_iter()
+                    .filter_map(|term| term.remove_none())
+                    .collect();
+                let k = k.saturating_sub(total - non_empty_terms.len());
+                if non_empty_terms.is_empty() || k == 0 {
+                    None
+                } else {
+                    Some(Term::Threshold {
+                        terms: non_empty_terms,
+                        k,
+                    })
+                }
+            }
         }
     }
 
@@ -41,6 +68,7 @@ impl Term {
     // 1. The operand of union is not union
     // 2. The operand of intersect is not intersect
     // 3. The number of operand of union or intersect is more than 1
+    // 4. A threshold is neither an union (k = 1) nor an intersect (k = n)
     pub fn flat(self) -> Term {
         // TODO: use a more suitable datastructure
         // flat can also be implemented on a mutable reference but need some
@@ -81,6 +109,80 @@ impl Term {
                     Term::Multiple { terms: flated, op }
                 }
             }
+            Term::Threshold { terms, k } => {
+                assert!(!terms.is_empty());
+
+                if k == 1 {
+                    Term::union(terms).flat()
+                } else if k == terms.len() {
+                    Term::intersect(terms).flat()
+                } else {
+                    Term::Threshold {
+                        terms: terms.into_iter().map(|term| term.flat()).collect(),
+                        k,
+                    }
+                }
+            }
+        }
+    }
+
+    // expand_threshold replaces every threshold with the union of every
+    // combination of k terms, which could be very large
+    pub fn expand_threshold(self) -> Term {
+        match self {
+            Term::None => Term::None,
+            Term::Unary { atom, op } => Term::Unary { atom, op },
+            Term::Multiple { terms, op } => Term::Multiple {
+                terms: terms
+                    .into_iter()
+                    .map(|term| term.expand_threshold())
+                    .collect(),
+                op,
+            },
+            Term::Threshold { terms, k } => {
+                let terms: Vec<Term> = terms
+                    .into_iter()
+                    .map(|term| term.expand_threshold())
+                    .collect();
+
+                Term::union(
+                    terms
+                        .into_iter()
+                        .combinations(k)
+                        .map(Term::intersect)
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    // force returns a term where the atom with the name is replaced by an atom
+    // which is always (or never) available
+    pub fn force(&self, name: &str, up: bool) -> Term {
+        match self {
+            Term::None => Term::None,
+            Term::Unary { atom, op } => {
+                if atom.name() == name {
+                    let probability = if up { 1.0 } else { 0.0 };
+                    Term::Unary {
+                        atom: Arc::new(Atom::new(name.to_string(), probability)),
+                        op: *op,
+                    }
+                } else {
+                    Term::Unary {
+                        atom: atom.clone(),
+                        op: *op,
+                    }
+                }
+            }
+            Term::Multiple { terms, op } => Term::Multiple {
+                terms: terms.iter().map(|term| term.force(name, up)).collect(),
+                op: *op,
+            },
+            Term::Threshold { terms, k } => Term::Threshold {
+                terms: terms.iter().map(|term| term.force(name, up)).collect(),
+                k: *k,
+            },
         }
     }
 
@@ -111,6 +213,15 @@ impl Term {
                     *op = MultiOp::Intersect
                 }
             }
+            Term::Threshold { terms, k } => {
+                // less than k of n terms are satisfied iff at least n - k + 1
+                // of them are not satisfied, and it's always true if k > n
+                for term in terms.iter_mut() {
+                    term.not();
+                }
+
+                *k = (terms.len() + 1).saturating_sub(*k);
+            }
         }
     }
 }
@@ -17,7 +17,7 @@ use std::{collections::BTreeMap, sync::Arc};
 
 use crate::calculate::{Atom, MultiOp, UnaryOp};
 
-use super::Term;
+use super::{Bdd, Term};
 
 use itertools::Itertools;
 
@@ -78,9 +78,37 @@ impl CalcMinimumUnit for Vec<Term> {
     }
 }
 
+// Engine is the algorithm to calculate the probability of a term
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Engine {
+    // expand unions and intersections with the inclusion–exclusion principle,
+    // which is exponential in the number of operands
+    InclusionExclusion,
+
+    // compile the term into a binary decision diagram, which is linear in the
+    // size of the diagram
+    #[default]
+    Bdd,
+}
+
 impl Term {
     pub fn calc(&self) -> f64 {
-        self.clone().flat().inner_calc()
+        self.calc_with(Engine::default())
+    }
+
+    pub fn calc_with(&self, engine: Engine) -> f64 {
+        if self.is_conditional() {
+            return self.condition(|term| term.calc_with(engine));
+        }
+
+        match engine {
+            // none is always satisfied, like an empty intersect
+            Engine::InclusionExclusion => match self.clone().remove_none() {
+                Some(term) => term.flat().inner_calc(),
+                None => 1.0,
+            },
+            Engine::Bdd => Bdd::compile(self).probability(),
+        }
     }
 
     // inner_calc receives a flat term without none and returns a probability
@@ -115,6 +143,23 @@ impl Term {
                 }
                 sum
             }
+            Term::Threshold { terms, k } => {
+                Term::calc_threshold(&terms, k, Engine::InclusionExclusion)
+            }
+            Term::Multiple {
+                terms,
+                op: MultiOp::Intersect,
+            } if terms
+                .iter()
+                .any(|term| matches!(term, Term::Threshold { .. })) =>
+            {
+                // the negation of a threshold is still a threshold, so it has
+                // to be expanded before applying De Morgan's laws
+                Term::intersect(terms)
+                    .expand_threshold()
+                    .flat()
+                    .inner_calc()
+            }
             Term::Multiple {
                 terms,
                 op: MultiOp::Intersect,
@@ -155,7 +200,9 @@ impl Term {
 
 #[cfg(test)]
 mod tests {
-    use crate::calculate::{AtomRegistry, Term};
+    use float_cmp::approx_eq;
+
+    use crate::calculate::{AtomRegistry, Engine, Term};
 
     #[test]
     fn test_calc() {
@@ -167,13 +214,55 @@ mod tests {
         let atom_a = registry.new_atom("atom_a".to_owned(), prob_a);
         let atom_b = registry.new_atom("atom_b".to_owned(), prob_b);
 
-        let union = Term::union(vec![Term::atom(atom_a.clone()), Term::atom(atom_b.clone())]);
-        assert_eq!(union.calc(), prob_a + prob_b - prob_a * prob_b);
+        for engine in [Engine::InclusionExclusion, Engine::Bdd] {
+            let union = Term::union(vec![Term::atom(atom_a.clone()), Term::atom(atom_b.clone())]);
+            assert_eq!(union.calc_with(engine), prob_a + prob_b - prob_a * prob_b);
+
+            let intersect =
+                Term::intersect(vec![Term::atom(atom_a.clone()), Term::atom(atom_b.clone())]);
+            assert_eq!(intersect.calc_with(engine), prob_a * prob_b);
 
-        let intersect = Term::intersect(vec![Term::atom(atom_a), Term::atom(atom_b)]);
-        assert_eq!(intersect.calc(), prob_a * prob_b);
+            let intersect_of_union = Term::intersect(vec![union.clone(), union]);
+            assert_eq!(
+                intersect_of_union.calc_with(engine),
+                prob_a + prob_b - prob_a * prob_b
+            );
+
+            // none is always satisfied
+            assert_eq!(Term::None.calc_with(engine), 1.0);
+            let intersect = Term::intersect(vec![Term::None, Term::atom(atom_a.clone())]);
+            assert_eq!(intersect.calc_with(engine), prob_a);
+        }
+    }
 
-        let intersect_of_union = Term::intersect(vec![union.clone(), union]);
-        assert_eq!(intersect_of_union.calc(), prob_a + prob_b - prob_a * prob_b);
+    #[test]
+    fn test_engines_with_none() {
+        let mut registry = AtomRegistry::default();
+        let a = Term::atom(registry.new_atom("a".to_owned(), 0.9));
+        let b = Term::atom(registry.new_atom("b".to_owned(), 0.8));
+
+        let terms = [
+            Term::None,
+            Term::union(vec![]),
+            Term::intersect(vec![]),
+            Term::union(vec![Term::None]),
+            Term::union(vec![Term::None, a.clone()]),
+            Term::intersect(vec![Term::None, a.clone(), b.clone()]),
+            Term::intersect(vec![Term::union(vec![a.clone(), Term::None]), b.clone()]),
+            Term::union(vec![Term::intersect(vec![Term::None]), a.clone()]),
+            Term::at_least(2, vec![Term::None, a.clone(), b.clone()]),
+            Term::at_least(3, vec![Term::None, a, b]),
+        ];
+        let expected = [1.0, 1.0, 1.0, 1.0, 1.0, 0.72, 0.8, 1.0, 0.98, 0.72];
+        for (term, expected) in terms.iter().zip(expected) {
+            for engine in [Engine::InclusionExclusion, Engine::Bdd] {
+                assert!(approx_eq!(
+                    f64,
+                    term.calc_with(engine),
+                    expected,
+                    epsilon = 0.0000001f64
+                ));
+            }
+        }
     }
 }
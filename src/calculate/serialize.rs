@@ -0,0 +1,272 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::check_sla;
+
+use super::{Atom, Condition, MultiOp, OutageModel, Term, UnaryOp};
+
+// SerializedTerm is the serialized form of a term. Every atom is serialized
+// once in `atoms`, and referred by its name in the tree, so the atoms shared
+// by many branches are not duplicated.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SerializedTerm {
+    // the atoms and their ancestors, and the parents of a conditional atom
+    // are placed before it
+    atoms: Vec<SerializedAtom>,
+    tree: Tree,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SerializedAtom {
+    name: String,
+    probability: f64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    condition: Option<SerializedCondition>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    outage: Option<OutageModel>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SerializedCondition {
+    parents: Vec<String>,
+    table: Vec<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Tree {
+    None,
+    Atom(String),
+    Not(String),
+    Union(Vec<Tree>),
+    Intersect(Vec<Tree>),
+    AtLeast { k: usize, terms: Vec<Tree> },
+}
+
+impl Tree {
+    fn from_term(term: &Term) -> Tree {
+        match term {
+            Term::None => Tree::None,
+            Term::Unary {
+                atom,
+                op: UnaryOp::None,
+            } => Tree::Atom(atom.name().to_string()),
+            Term::Unary {
+                atom,
+                op: UnaryOp::Not,
+            } => Tree::Not(atom.name().to_string()),
+            Term::Multiple {
+                terms,
+                op: MultiOp::Union,
+            } => Tree::Union(terms.iter().map(Tree::from_term).collect()),
+            Term::Multiple {
+                terms,
+                op: MultiOp::Intersect,
+            } => Tree::Intersect(terms.iter().map(Tree::from_term).collect()),
+            Term::Threshold { terms, k } => Tree::AtLeast {
+                k: *k,
+                terms: terms.iter().map(Tree::from_term).collect(),
+            },
+        }
+    }
+
+    fn into_term<E: Error>(self, atoms: &HashMap<String, Arc<Atom>>) -> Result<Term, E> {
+        let atom = |name: String| {
+            atoms
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| E::custom(format!("atom {} is not defined", name)))
+        };
+        let terms = |trees: Vec<Tree>| {
+            trees
+                .into_iter()
+                .map(|tree| tree.into_term(atoms))
+                .collect::<Result<Vec<_>, E>>()
+        };
+
+        Ok(match self {
+            Tree::None => Term::None,
+            Tree::Atom(name) => Term::atom(atom(name)?),
+            Tree::Not(name) => Term::Unary {
+                atom: atom(name)?,
+                op: UnaryOp::Not,
+            },
+            Tree::Union(trees) => Term::union(terms(trees)?),
+            Tree::Intersect(trees) => Term::intersect(terms(trees)?),
+            Tree::AtLeast { k, terms: trees } => Term::at_least(k, terms(trees)?),
+        })
+    }
+}
+
+impl Serialize for Term {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let atoms = self
+            .network()
+            .iter()
+            .map(|atom| SerializedAtom {
+                name: atom.name().to_string(),
+                probability: atom.probability(),
+                condition: atom.condition().map(|condition| SerializedCondition {
+                    parents: condition
+                        .parents()
+                        .iter()
+                        .map(|parent| parent.name().to_string())
+                        .collect(),
+                    table: condition.table().to_vec(),
+                }),
+                outage: atom.outage(),
+            })
+            .collect();
+
+        SerializedTerm {
+            atoms,
+            tree: Tree::from_term(self),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Term {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized = SerializedTerm::deserialize(deserializer)?;
+
+        let mut atoms: HashMap<String, Arc<Atom>> = HashMap::new();
+        for atom in serialized.atoms {
+            check_sla(&atom.name, atom.probability).map_err(D::Error::custom)?;
+            let condition = match atom.condition {
+                Some(condition) => {
+                    let parents = condition
+                        .parents
+                        .iter()
+                        .map(|parent| {
+                            atoms.get(parent).cloned().ok_or_else(|| {
+                                D::Error::custom(format!(
+                                    "parent {} of {} should be defined before it",
+                                    parent, atom.name
+                                ))
+                            })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Some(
+                        Condition::new(&atom.name, parents, condition.table)
+                            .map_err(D::Error::custom)?,
+                    )
+                }
+                None => None,
+            };
+
+            let name = atom.name.clone();
+            let atom = Atom::with_parts(atom.name, atom.probability, condition, atom.outage);
+            if atoms.insert(name.clone(), Arc::new(atom)).is_some() {
+                return Err(D::Error::custom(format!("atom {} is defined twice", name)));
+            }
+        }
+
+        serialized.tree.into_term(&atoms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        calculate::{AtomRegistry, DumpTerm, Term},
+        error::SlacError,
+        sla::{Dependency, Group, Service},
+    };
+
+    #[test]
+    fn test_serialize_term() {
+        let mut registry = AtomRegistry::default();
+        let database = registry.new_atom("database".to_owned(), 0.9);
+        let cache = registry.new_conditional_atom(
+            "cache".to_owned(),
+            vec![database.clone()],
+            vec![0.5, 0.99],
+        );
+        let replicas: Vec<Term> = (0..3)
+            .map(|index| Term::atom(registry.new_atom(format!("replica_{}", index), 0.95)))
+            .collect();
+        let term = Term::intersect(vec![
+            Term::union(vec![Term::atom(database.clone()), Term::atom(cache)]),
+            Term::at_least(2, replicas),
+            Term::atom(database),
+        ]);
+
+        let json = serde_json::to_string(&term).unwrap();
+        // the shared atom is serialized only once
+        assert_eq!(json.matches(r#""name":"database""#).count(), 1);
+
+        let deserialized: Term = serde_json::from_str(&json).unwrap();
+        assert_eq!(term.calc(), deserialized.calc());
+        assert_eq!(json, serde_json::to_string(&deserialized).unwrap());
+
+        let json = r#"{"atoms": [], "tree": {"atom": "unknown"}}"#;
+        assert!(serde_json::from_str::<Term>(json).is_err());
+
+        let json = r#"{"atoms": [{"name": "a", "probability": 1.5}], "tree": {"atom": "a"}}"#;
+        assert!(serde_json::from_str::<Term>(json).is_err());
+    }
+
+    #[test]
+    fn test_serialize_service() {
+        let infra = Service::known_sla("infra", 0.99);
+        let members = (0..3)
+            .map(|index| {
+                Service::dependencies(vec![
+                    Dependency::Service(infra.clone()),
+                    Dependency::Service(Service::known_sla(format!("replica_{}", index), 0.9)),
+                ])
+            })
+            .collect();
+        let service = Service::dependencies(vec![Dependency::Group(Group::new(members, 2))]);
+
+        let json = serde_json::to_string(&service).unwrap();
+        let deserialized: Service = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.validate().is_ok());
+
+        let calc = |service: &Service| service.dump_term(&mut AtomRegistry::default()).calc();
+        assert_eq!(calc(&service), calc(&deserialized));
+
+        // a deserialized group skips the checks of its constructor
+        let members = (0..2)
+            .map(|index| Service::known_sla(format!("replica_{}", index), 0.9))
+            .collect();
+        let service = Service::dependencies(vec![Dependency::Group(Group::with_common_cause(
+            members, 1, 0.5,
+        ))]);
+        let json = serde_json::to_string(&service).unwrap();
+        let invalid: Service =
+            serde_json::from_str(&json.replace("\"beta\":0.5", "\"beta\":1.5")).unwrap();
+        assert_eq!(
+            invalid.validate(),
+            Err(SlacError::InvalidBeta { beta: 1.5 })
+        );
+        let invalid: Service =
+            serde_json::from_str(&json.replacen("\"sla\":0.9", "\"sla\":0.8", 1)).unwrap();
+        assert!(matches!(
+            invalid.validate(),
+            Err(SlacError::NonIdenticalMember { .. })
+        ));
+    }
+}
@@ -13,15 +13,64 @@
 // limitations under the License.
 //
 
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::error::{check_sla, SlacError};
+
+use super::{Condition, OutageModel, MONTH};
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Atom {
     probability: f64,
     name: String,
+
+    // the atom depends on other atoms, and the probability is the marginal one
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    condition: Option<Condition>,
+
+    // how the atom fails and recovers over time, if it's known
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    outage: Option<OutageModel>,
+
+    // the exact probability, if the atom is registered with a rational one
+    #[cfg(feature = "exact")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) exact: Option<super::BigRational>,
 }
 
 impl Atom {
+    pub(crate) fn new(name: String, probability: f64) -> Atom {
+        Atom::with_parts(name, probability, None, None)
+    }
+
+    pub(crate) fn with_parts(
+        name: String,
+        probability: f64,
+        condition: Option<Condition>,
+        outage: Option<OutageModel>,
+    ) -> Atom {
+        Atom {
+            probability,
+            name,
+            condition,
+            outage,
+            #[cfg(feature = "exact")]
+            exact: None,
+        }
+    }
+
     /// Get a reference to the atom's name.
     pub fn name(&self) -> &str {
         self.name.as_ref()
@@ -31,6 +80,23 @@ impl Atom {
     pub fn probability(&self) -> f64 {
         self.probability
     }
+
+    // unavailability returns 1 - probability, which is exact for the
+    // probabilities in [0.5, 1], as the subtraction of two close floating
+    // numbers is exact
+    pub fn unavailability(&self) -> f64 {
+        1.0 - self.probability
+    }
+
+    /// Get a reference to the atom's conditional probability table.
+    pub fn condition(&self) -> Option<&Condition> {
+        self.condition.as_ref()
+    }
+
+    /// Get the atom's outage model.
+    pub fn outage(&self) -> Option<OutageModel> {
+        self.outage
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
@@ -47,13 +113,16 @@ pub enum MultiOp {
 
 #[derive(Debug, Clone)]
 pub enum Term {
-    // none is a special case that represents the empty set
-    // anything calculate with none results in itself
+    // none is a special case that is always satisfied, e.g. a service without
+    // any dependency. It's ignored by an intersect, and satisfies a union.
     None,
 
     Unary { atom: Arc<Atom>, op: UnaryOp },
 
     Multiple { terms: Vec<Term>, op: MultiOp },
+
+    // threshold is satisfied iff at least k of the terms are satisfied
+    Threshold { terms: Vec<Term>, k: usize },
 }
 
 impl Term {
@@ -75,25 +144,149 @@ impl Term {
         }
     }
 
+    pub fn at_least(k: usize, terms: Vec<Term>) -> Self {
+        Self::Threshold { terms, k }
+    }
+
     pub fn atom(atom: Arc<Atom>) -> Self {
         Self::Unary {
             atom,
             op: UnaryOp::None,
         }
     }
+
+    // atoms returns every distinct atom in this term, in the order of their
+    // first appearance
+    pub fn atoms(&self) -> Vec<Arc<Atom>> {
+        fn collect(term: &Term, seen: &mut HashSet<String>, atoms: &mut Vec<Arc<Atom>>) {
+            match term {
+                Term::None => {}
+                Term::Unary { atom, op: _ } => {
+                    if seen.insert(atom.name().to_string()) {
+                        atoms.push(atom.clone());
+                    }
+                }
+                Term::Multiple { terms, op: _ } | Term::Threshold { terms, k: _ } => {
+                    for term in terms {
+                        collect(term, seen, atoms);
+                    }
+                }
+            }
+        }
+
+        let mut atoms = Vec::new();
+        collect(self, &mut HashSet::new(), &mut atoms);
+        atoms
+    }
 }
 
-#[derive(Default)]
 pub struct AtomRegistry {
-    registry: HashMap<String, Arc<Atom>>,
+    pub(super) registry: HashMap<String, Arc<Atom>>,
+
+    // the period in which the probability of a component is derived from its
+    // failure rate, a month by default
+    mission_time: Duration,
+}
+
+impl Default for AtomRegistry {
+    fn default() -> Self {
+        AtomRegistry::with_mission_time(MONTH)
+    }
 }
 
 impl AtomRegistry {
+    pub fn with_mission_time(mission_time: Duration) -> AtomRegistry {
+        AtomRegistry {
+            registry: HashMap::new(),
+            mission_time,
+        }
+    }
+
+    /// Get the mission time.
+    pub fn mission_time(&self) -> Duration {
+        self.mission_time
+    }
+
+    // get returns the registered atom with the name
+    pub fn get(&self, name: &str) -> Option<Arc<Atom>> {
+        self.registry.get(name).cloned()
+    }
+
+    // new_atom returns the atom with the name, or registers a new one. It
+    // panics if the probability is invalid, or conflicts with the registered
+    // atom, see `try_new_atom` for the fallible version.
     pub fn new_atom(&mut self, name: String, probability: f64) -> Arc<Atom> {
-        self.registry
+        self.try_new_atom(name, probability)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new_atom(&mut self, name: String, probability: f64) -> Result<Arc<Atom>, SlacError> {
+        check_sla(&name, probability)?;
+
+        let atom = self
+            .registry
             .entry(name.clone())
-            .or_insert_with(|| Arc::new(Atom { name, probability }))
-            .clone()
+            .or_insert_with(|| Arc::new(Atom::new(name, probability)));
+        if atom.probability() != probability {
+            return Err(SlacError::ConflictingAtom {
+                name: atom.name().to_string(),
+                existing: atom.probability(),
+                requested: probability,
+            });
+        }
+
+        Ok(atom.clone())
+    }
+
+    // new_atom_with_outage registers an atom which fails and recovers as the
+    // outage model, and its probability is the steady-state availability
+    pub fn new_atom_with_outage(&mut self, name: String, outage: OutageModel) -> Arc<Atom> {
+        let probability = outage.probability();
+        if !self.registry.contains_key(&name) {
+            let atom = Atom::with_parts(name.clone(), probability, None, Some(outage));
+            self.registry.insert(name.clone(), Arc::new(atom));
+        }
+
+        self.new_atom(name, probability)
+    }
+
+    // new_conditional_atom registers an atom which depends on the parents,
+    // see `Condition` for the layout of the table
+    pub fn new_conditional_atom(
+        &mut self,
+        name: String,
+        parents: Vec<Arc<Atom>>,
+        table: Vec<f64>,
+    ) -> Arc<Atom> {
+        self.try_new_conditional_atom(name, parents, table)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new_conditional_atom(
+        &mut self,
+        name: String,
+        parents: Vec<Arc<Atom>>,
+        table: Vec<f64>,
+    ) -> Result<Arc<Atom>, SlacError> {
+        let condition = Condition::new(&name, parents, table)?;
+
+        // the marginal probability is calculated by conditioning on the
+        // parents, the placeholder probability is never used
+        let placeholder = Atom::with_parts(name.clone(), f64::NAN, Some(condition.clone()), None);
+        let probability = Term::atom(Arc::new(placeholder)).calc();
+
+        let atom = self.registry.entry(name.clone()).or_insert_with(|| {
+            Arc::new(Atom::with_parts(name, probability, Some(condition), None))
+        });
+        if atom.probability() != probability {
+            return Err(SlacError::ConflictingAtom {
+                name: atom.name().to_string(),
+                existing: atom.probability(),
+                requested: probability,
+            });
+        }
+
+        Ok(atom.clone())
     }
 }
 
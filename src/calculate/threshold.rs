@@ -0,0 +1,134 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{collections::HashMap, sync::Arc};
+
+use super::{Atom, Engine, Term};
+
+// at_least calculates the probability that at least k of the independent
+// events happen, in O(n·k)
+fn at_least<I: IntoIterator<Item = f64>>(k: usize, probabilities: I) -> f64 {
+    // dp[j] is the probability that exactly j of the visited events happen,
+    // except that dp[k] is the probability that at least k of them happen
+    let mut dp = vec![0f64; k + 1];
+    dp[0] = 1.0;
+
+    for p in probabilities {
+        dp[k] += dp[k - 1] * p;
+        for j in (1..k).rev() {
+            dp[j] = dp[j] * (1.0 - p) + dp[j - 1] * p;
+        }
+        dp[0] *= 1.0 - p;
+    }
+
+    dp[k]
+}
+
+// shared_atom returns the uncertain atom which appears in most of the terms,
+// if there is any atom appearing in more than one term
+fn shared_atom(terms: &[Term]) -> Option<Arc<Atom>> {
+    let mut count: HashMap<String, (usize, Arc<Atom>)> = HashMap::new();
+    for term in terms {
+        for atom in term.atoms() {
+            // an atom with probability 0 or 1 is independent with anything
+            if atom.probability() == 0.0 || atom.probability() == 1.0 {
+                continue;
+            }
+
+            count
+                .entry(atom.name().to_string())
+                .or_insert_with(|| (0, atom.clone()))
+                .0 += 1;
+        }
+    }
+
+    count
+        .into_values()
+        .filter(|(count, _)| *count > 1)
+        .max_by(|(a, atom_a), (b, atom_b)| a.cmp(b).then(atom_b.name().cmp(atom_a.name())))
+        .map(|(_, atom)| atom)
+}
+
+impl Term {
+    // calc_threshold calculates the probability that at least k of the terms
+    // are satisfied. If the terms are independent, it's calculated with the
+    // recurrence directly, or the shared atoms are conditioned on one by one
+    // until the terms become independent.
+    pub(crate) fn calc_threshold(terms: &[Term], k: usize, engine: Engine) -> f64 {
+        if k == 0 {
+            return 1.0;
+        }
+        if k > terms.len() {
+            return 0.0;
+        }
+
+        match shared_atom(terms) {
+            None => at_least(k, terms.iter().map(|term| term.calc_with(engine))),
+            Some(atom) => {
+                let up: Vec<Term> = terms
+                    .iter()
+                    .map(|term| term.force(atom.name(), true))
+                    .collect();
+                let down: Vec<Term> = terms
+                    .iter()
+                    .map(|term| term.force(atom.name(), false))
+                    .collect();
+
+                atom.probability() * Term::calc_threshold(&up, k, engine)
+                    + (1.0 - atom.probability()) * Term::calc_threshold(&down, k, engine)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+    use rand::Rng;
+
+    use crate::calculate::{AtomRegistry, Engine, Term};
+
+    #[test]
+    fn test_threshold() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..5 {
+            let mut registry = AtomRegistry::default();
+            let shared = Term::atom(registry.new_atom("shared".to_owned(), rng.gen()));
+
+            // every member depends on its own atom, and half of them also
+            // depend on the shared atom
+            let members: Vec<Term> = (0..7)
+                .map(|index| {
+                    let own = Term::atom(registry.new_atom(format!("atom_{}", index), rng.gen()));
+                    if index % 2 == 0 {
+                        Term::intersect(vec![own, shared.clone()])
+                    } else {
+                        own
+                    }
+                })
+                .collect();
+
+            for k in 1..=7 {
+                let threshold = Term::at_least(k, members.clone());
+                let expected = threshold.clone().expand_threshold().calc_with(Engine::Bdd);
+
+                for engine in [Engine::InclusionExclusion, Engine::Bdd] {
+                    let got = threshold.calc_with(engine);
+                    assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64));
+                }
+            }
+        }
+    }
+}
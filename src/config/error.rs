@@ -0,0 +1,111 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{fmt, path::PathBuf};
+
+use crate::error::SlacError;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    UnknownFormat(PathBuf),
+    Parse(String),
+
+    DuplicateName(String),
+    // a service must have exactly one of a predefined sla, mtbf and mttr, a
+    // failure rate or a list of dependencies
+    AmbiguousService(String),
+    // fault domains are only allowed on a service with a predefined sla or a
+    // failure model
+    UnexpectedDomains(String),
+    // credits are only allowed on a service with a predefined sla
+    UnexpectedCredits(String),
+    UnknownReference {
+        from: String,
+        to: String,
+    },
+    // the referred object is not the expected kind, e.g. a group member is
+    // an infra
+    UnexpectedReference {
+        from: String,
+        to: String,
+        expected: &'static str,
+    },
+    // the names on a dependency cycle, the first one is repeated at the end
+    Cycle(Vec<String>),
+    UnknownTarget(String),
+    // the definition is rejected by the `sla` model
+    Invalid {
+        name: String,
+        source: SlacError,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "failed to read config: {}", err),
+            ConfigError::UnknownFormat(path) => write!(
+                f,
+                "cannot infer the format of {}, expected a .yaml, .yml, .toml or .json file",
+                path.display()
+            ),
+            ConfigError::Parse(msg) => write!(f, "failed to parse config: {}", msg),
+            ConfigError::DuplicateName(name) => {
+                write!(f, "name {} is defined more than once", name)
+            }
+            ConfigError::AmbiguousService(name) => write!(
+                f,
+                "service {} should have exactly one of sla, mtbf and mttr, failure rate or dependencies",
+                name
+            ),
+            ConfigError::UnexpectedDomains(name) => write!(
+                f,
+                "service {} has fault domains, but only a service with an sla or a failure model could be placed in fault domains",
+                name
+            ),
+            ConfigError::UnexpectedCredits(name) => write!(
+                f,
+                "service {} doesn't have a predefined sla, only a service with sla could have credits",
+                name
+            ),
+            ConfigError::UnknownReference { from, to } => {
+                write!(f, "{} refers to {}, which is not defined", from, to)
+            }
+            ConfigError::UnexpectedReference { from, to, expected } => {
+                write!(f, "{} refers to {}, which is not a {}", from, to, expected)
+            }
+            ConfigError::Cycle(path) => write!(f, "dependency cycle: {}", path.join(" -> ")),
+            ConfigError::UnknownTarget(name) => write!(f, "target {} is not defined", name),
+            ConfigError::Invalid { name, source } => write!(f, "invalid {}: {}", name, source),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(err) => Some(err),
+            ConfigError::Invalid { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(err: std::io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{path::Path, time::Duration};
+
+use serde::{de::Error, Deserialize, Deserializer};
+
+use crate::sla::DomainKind;
+
+use super::ConfigError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl Format {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Format, ConfigError> {
+        let path = path.as_ref();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml") | Some("yml") => Ok(Format::Yaml),
+            Some("toml") => Ok(Format::Toml),
+            Some("json") => Ok(Format::Json),
+            _ => Err(ConfigError::UnknownFormat(path.to_path_buf())),
+        }
+    }
+}
+
+// Config is the file representation of a cluster. Every object is referred by
+// its name, rather than the `Arc` wiring used by the `sla` model, so that it
+// can be generated by a script easily.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    // the service to calculate, all services which are not depended by others
+    // will be calculated if it's not specified
+    #[serde(default)]
+    pub target: Option<String>,
+
+    #[serde(default)]
+    pub services: Vec<ServiceConfig>,
+
+    #[serde(default)]
+    pub groups: Vec<GroupConfig>,
+
+    #[serde(default)]
+    pub infras: Vec<InfraConfig>,
+
+    #[serde(default)]
+    pub connections: Vec<ConnectionConfig>,
+
+    #[serde(default)]
+    pub programs: Vec<ProgramConfig>,
+
+    #[serde(default)]
+    pub fault_domains: Vec<FaultDomainConfig>,
+
+    // the credit tiers offered to the customers of the targets
+    #[serde(default)]
+    pub credits: Vec<CreditTierConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceConfig {
+    pub name: String,
+
+    // the predefined sla of an external service, as a number or a percentage
+    #[serde(default, deserialize_with = "optional_sla")]
+    pub sla: Option<f64>,
+
+    // the mean time between failures and the mean time to repair, like
+    // "720h" and "30m", to derive the sla of a repairable service
+    #[serde(default, deserialize_with = "optional_duration")]
+    pub mtbf: Option<Duration>,
+
+    #[serde(default, deserialize_with = "optional_duration")]
+    pub mttr: Option<Duration>,
+
+    // failures per hour, to derive the sla of a service which is not
+    // repaired in the mission time
+    #[serde(default)]
+    pub failure_rate: Option<f64>,
+
+    // names of the services or groups this service depends on
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+
+    // names of the fault domains this service runs in, only allowed with a
+    // predefined sla
+    #[serde(default)]
+    pub domains: Vec<String>,
+
+    // the credit tiers paid by the provider, only allowed with a predefined
+    // sla
+    #[serde(default)]
+    pub credits: Vec<CreditTierConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreditTierConfig {
+    // the credit is paid when the availability is lower than this
+    #[serde(deserialize_with = "sla")]
+    pub below: f64,
+
+    // the fraction of the bill, as a number or a percentage
+    #[serde(deserialize_with = "sla")]
+    pub credit: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupConfig {
+    pub name: String,
+
+    // names of the services in this group
+    pub members: Vec<String>,
+
+    pub quorum: usize,
+
+    // the beta factor of the common cause failure, the members should be
+    // services with the same predefined sla
+    #[serde(default, deserialize_with = "optional_sla")]
+    pub beta: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InfraConfig {
+    pub name: String,
+
+    #[serde(deserialize_with = "sla")]
+    pub sla: f64,
+
+    // names of the fault domains this infra runs in
+    #[serde(default)]
+    pub domains: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionConfig {
+    pub name: String,
+
+    #[serde(deserialize_with = "sla")]
+    pub sla: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FaultDomainConfig {
+    pub name: String,
+
+    // one of "region", "zone" or "rack"
+    #[serde(deserialize_with = "domain_kind")]
+    pub kind: DomainKind,
+
+    #[serde(deserialize_with = "sla")]
+    pub sla: f64,
+
+    // name of the domain containing this one, e.g. the region of a zone
+    #[serde(default)]
+    pub parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramConfig {
+    pub name: String,
+
+    // name of the infra this program runs on
+    pub infra: String,
+
+    #[serde(default)]
+    pub dependencies: Vec<ProgramDependencyConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProgramDependencyConfig {
+    // name of the service, program or group this program depends on
+    pub to: String,
+
+    // name of the connection to reach the dependency
+    pub via: String,
+}
+
+// parse_sla parses a decimal like "0.9995" or a percentage like "99.95%". The
+// percentage is shifted in the decimal representation, so "99.95%" results in
+// exactly the same floating number as "0.9995".
+pub fn parse_sla(text: &str) -> Option<f64> {
+    let text = text.trim();
+    match text.strip_suffix('%') {
+        Some(percentage) => format!("{}e-2", percentage.trim_end()).parse().ok(),
+        None => text.parse().ok(),
+    }
+}
+
+// parse_duration parses a number with a unit like "90s", "30m", "4h", "30d",
+// "2w" or "1y", where a year is 365 days
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let unit = text.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let seconds = match unit.trim() {
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "d" => 86400.0,
+        "w" => 7.0 * 86400.0,
+        "y" => 365.0 * 86400.0,
+        _ => return None,
+    };
+
+    let value: f64 = text[..text.len() - unit.len()].parse().ok()?;
+    Duration::try_from_secs_f64(value * seconds).ok()
+}
+
+fn optional_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    parse_duration(&text)
+        .map(Some)
+        .ok_or_else(|| D::Error::custom(format!("invalid duration {:?}", text)))
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Sla {
+    Number(f64),
+    Text(String),
+}
+
+fn sla<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    match Sla::deserialize(deserializer)? {
+        Sla::Number(sla) => Ok(sla),
+        Sla::Text(text) => {
+            parse_sla(&text).ok_or_else(|| D::Error::custom(format!("invalid sla {:?}", text)))
+        }
+    }
+}
+
+fn domain_kind<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DomainKind, D::Error> {
+    let text = String::deserialize(deserializer)?;
+    match text.as_str() {
+        "region" => Ok(DomainKind::Region),
+        "zone" => Ok(DomainKind::Zone),
+        "rack" => Ok(DomainKind::Rack),
+        _ => Err(D::Error::unknown_variant(
+            &text,
+            &["region", "zone", "rack"],
+        )),
+    }
+}
+
+fn optional_sla<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    sla(deserializer).map(Some)
+}
+
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
+        let format = Format::from_path(&path)?;
+        let content = std::fs::read_to_string(path)?;
+
+        Config::parse(&content, format)
+    }
+
+    pub fn parse(content: &str, format: Format) -> Result<Config, ConfigError> {
+        match format {
+            Format::Yaml => {
+                serde_yaml::from_str(content).map_err(|err| ConfigError::Parse(err.to_string()))
+            }
+            Format::Toml => {
+                toml::from_str(content).map_err(|err| ConfigError::Parse(err.to_string()))
+            }
+            Format::Json => {
+                serde_json::from_str(content).map_err(|err| ConfigError::Parse(err.to_string()))
+            }
+        }
+    }
+}
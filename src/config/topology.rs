@@ -0,0 +1,868 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    calculate::{CreditPolicy, CreditTier},
+    error::SlacError,
+    sla::{Connection, Dependency, FailureModel, FaultDomain, Group, Infra, Service, ServiceGraph},
+};
+
+use super::{
+    Config, ConfigError, ConnectionConfig, CreditTierConfig, FaultDomainConfig, GroupConfig,
+    InfraConfig, ProgramConfig, ServiceConfig,
+};
+
+// Topology is the `sla` model built from a `Config`, with every object
+// indexed by its name.
+pub struct Topology {
+    services: BTreeMap<String, Arc<Service>>,
+    groups: BTreeMap<String, Arc<Group>>,
+    targets: Vec<String>,
+    credits: Option<CreditPolicy>,
+}
+
+impl Topology {
+    pub fn service(&self, name: &str) -> Option<&Arc<Service>> {
+        self.services.get(name)
+    }
+
+    pub fn group(&self, name: &str) -> Option<&Arc<Group>> {
+        self.groups.get(name)
+    }
+
+    // targets returns the services which should be calculated, in the order
+    // of their definitions
+    pub fn targets(&self) -> impl Iterator<Item = (&str, &Arc<Service>)> {
+        self.targets
+            .iter()
+            .map(|name| (name.as_str(), &self.services[name]))
+    }
+
+    // credits returns the credit tiers offered to the customers of the
+    // targets
+    pub fn credits(&self) -> Option<&CreditPolicy> {
+        self.credits.as_ref()
+    }
+
+    // graph draws the dependency graph of the services, in which the services
+    // and groups are labelled with their names in the config
+    pub fn graph<'a, I: IntoIterator<Item = &'a Arc<Service>>>(
+        &self,
+        services: I,
+        mission_time: Duration,
+    ) -> ServiceGraph {
+        let mut graph = ServiceGraph::new(mission_time);
+        for (name, service) in self.services.iter() {
+            graph.name_service(service, name);
+        }
+        for (name, group) in self.groups.iter() {
+            graph.name_group(group, name);
+        }
+
+        for service in services {
+            graph.add(service);
+        }
+        graph
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Definition<'a> {
+    Service(&'a ServiceConfig),
+    Group(&'a GroupConfig),
+    Infra(&'a InfraConfig),
+    Connection(&'a ConnectionConfig),
+    Program(&'a ProgramConfig),
+    FaultDomain(&'a FaultDomainConfig),
+}
+
+impl<'a> Definition<'a> {
+    fn name(&self) -> &'a str {
+        match self {
+            Definition::Service(service) => &service.name,
+            Definition::Group(group) => &group.name,
+            Definition::Infra(infra) => &infra.name,
+            Definition::Connection(connection) => &connection.name,
+            Definition::Program(program) => &program.name,
+            Definition::FaultDomain(domain) => &domain.name,
+        }
+    }
+}
+
+struct Resolver<'a> {
+    definitions: HashMap<&'a str, Definition<'a>>,
+
+    // programs are also stored in services
+    services: BTreeMap<String, Arc<Service>>,
+    groups: BTreeMap<String, Arc<Group>>,
+    infras: BTreeMap<String, Arc<Infra>>,
+    connections: BTreeMap<String, Arc<Connection>>,
+    domains: BTreeMap<String, Arc<FaultDomain>>,
+
+    // the stack of names which are being resolved, a name which is resolved
+    // again before leaving the stack is on a dependency cycle
+    resolving: Vec<&'a str>,
+}
+
+impl<'a> Resolver<'a> {
+    fn definition(&self, from: &str, name: &str) -> Result<Definition<'a>, ConfigError> {
+        self.definitions
+            .get(name)
+            .copied()
+            .ok_or_else(|| ConfigError::UnknownReference {
+                from: from.to_string(),
+                to: name.to_string(),
+            })
+    }
+
+    fn resolve_dependency(&mut self, from: &str, name: &str) -> Result<Dependency, ConfigError> {
+        match self.definition(from, name)? {
+            Definition::Group(group) => Ok(Dependency::Group(self.resolve_group(group)?)),
+            definition => Ok(Dependency::Service(self.resolve(
+                from,
+                definition,
+                "service, program or group",
+            )?)),
+        }
+    }
+
+    // resolve resolves a definition which could be used as a `Service`
+    fn resolve(
+        &mut self,
+        from: &str,
+        definition: Definition<'a>,
+        expected: &'static str,
+    ) -> Result<Arc<Service>, ConfigError> {
+        match definition {
+            Definition::Service(service) => self.resolve_service(service),
+            Definition::Program(program) => self.resolve_program(program),
+            _ => Err(ConfigError::UnexpectedReference {
+                from: from.to_string(),
+                to: definition.name().to_string(),
+                expected,
+            }),
+        }
+    }
+
+    fn enter(&mut self, name: &'a str) -> Result<(), ConfigError> {
+        if let Some(start) = self.resolving.iter().position(|item| *item == name) {
+            let mut path: Vec<String> = self.resolving[start..]
+                .iter()
+                .map(|item| item.to_string())
+                .collect();
+            path.push(name.to_string());
+            return Err(ConfigError::Cycle(path));
+        }
+
+        self.resolving.push(name);
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.resolving.pop();
+    }
+
+    fn resolve_service(&mut self, config: &'a ServiceConfig) -> Result<Arc<Service>, ConfigError> {
+        if let Some(service) = self.services.get(&config.name) {
+            return Ok(service.clone());
+        }
+        self.enter(&config.name)?;
+
+        let ambiguous = || ConfigError::AmbiguousService(config.name.clone());
+        let model = match (config.mtbf, config.mttr, config.failure_rate) {
+            (None, None, None) => None,
+            (Some(mtbf), Some(mttr), None) => Some(FailureModel::Repairable { mtbf, mttr }),
+            (None, None, Some(rate)) => Some(FailureModel::FailureRate { rate }),
+            _ => return Err(ambiguous()),
+        };
+
+        let service = match (config.sla, model) {
+            (Some(_), Some(_)) => return Err(ambiguous()),
+            (Some(_), None) | (None, Some(_)) if !config.dependencies.is_empty() => {
+                return Err(ambiguous())
+            }
+            (Some(sla), None) => {
+                let domains = self.resolve_domains(&config.name, &config.domains)?;
+                match credit_policy(&config.name, &config.credits)? {
+                    Some(credits) => Service::try_with_credits(&config.name, sla, domains, credits),
+                    None => Service::try_in_domains(&config.name, sla, domains),
+                }
+                .map_err(|err| invalid(&config.name, err))?
+            }
+            (None, _) if !config.credits.is_empty() => {
+                return Err(ConfigError::UnexpectedCredits(config.name.clone()))
+            }
+            (None, Some(model)) => {
+                let domains = self.resolve_domains(&config.name, &config.domains)?;
+                Service::try_modeled(&config.name, model, domains)
+                    .map_err(|err| invalid(&config.name, err))?
+            }
+            (None, None) if !config.domains.is_empty() => {
+                return Err(ConfigError::UnexpectedDomains(config.name.clone()))
+            }
+            (None, None) if config.dependencies.is_empty() => return Err(ambiguous()),
+            (None, None) => {
+                let mut dependencies = Vec::new();
+                for name in config.dependencies.iter() {
+                    dependencies.push(self.resolve_dependency(&config.name, name)?);
+                }
+                Service::dependencies(dependencies)
+            }
+        };
+
+        self.leave();
+        self.services.insert(config.name.clone(), service.clone());
+        Ok(service)
+    }
+
+    fn resolve_group(&mut self, config: &'a GroupConfig) -> Result<Arc<Group>, ConfigError> {
+        if let Some(group) = self.groups.get(&config.name) {
+            return Ok(group.clone());
+        }
+        self.enter(&config.name)?;
+
+        let mut members = Vec::new();
+        for name in config.members.iter() {
+            let definition = self.definition(&config.name, name)?;
+            members.push(self.resolve(&config.name, definition, "service or program")?);
+        }
+        let group = match config.beta {
+            Some(beta) => Group::try_with_common_cause(members, config.quorum, beta),
+            None => Group::try_new(members, config.quorum),
+        }
+        .map_err(|err| invalid(&config.name, err))?;
+
+        self.leave();
+        self.groups.insert(config.name.clone(), group.clone());
+        Ok(group)
+    }
+
+    fn resolve_infra(&mut self, from: &str, name: &str) -> Result<Arc<Infra>, ConfigError> {
+        match self.definition(from, name)? {
+            Definition::Infra(config) => {
+                if let Some(infra) = self.infras.get(&config.name) {
+                    return Ok(infra.clone());
+                }
+                let domains = self.resolve_domains(&config.name, &config.domains)?;
+                let infra = Infra::try_in_domains(&config.name, config.sla, domains)
+                    .map_err(|err| invalid(&config.name, err))?;
+                self.infras.insert(config.name.clone(), infra.clone());
+                Ok(infra)
+            }
+            _ => Err(ConfigError::UnexpectedReference {
+                from: from.to_string(),
+                to: name.to_string(),
+                expected: "infra",
+            }),
+        }
+    }
+
+    fn resolve_connection(
+        &mut self,
+        from: &str,
+        name: &str,
+    ) -> Result<Arc<Connection>, ConfigError> {
+        match self.definition(from, name)? {
+            Definition::Connection(config) => {
+                if let Some(connection) = self.connections.get(&config.name) {
+                    return Ok(connection.clone());
+                }
+                let connection = Connection::try_new(&config.name, config.sla)
+                    .map_err(|err| invalid(&config.name, err))?;
+                self.connections
+                    .insert(config.name.clone(), connection.clone());
+                Ok(connection)
+            }
+            _ => Err(ConfigError::UnexpectedReference {
+                from: from.to_string(),
+                to: name.to_string(),
+                expected: "connection",
+            }),
+        }
+    }
+
+    fn resolve_domain(&mut self, from: &str, name: &str) -> Result<Arc<FaultDomain>, ConfigError> {
+        let config = match self.definition(from, name)? {
+            Definition::FaultDomain(config) => config,
+            _ => {
+                return Err(ConfigError::UnexpectedReference {
+                    from: from.to_string(),
+                    to: name.to_string(),
+                    expected: "fault domain",
+                })
+            }
+        };
+        if let Some(domain) = self.domains.get(&config.name) {
+            return Ok(domain.clone());
+        }
+        self.enter(&config.name)?;
+
+        let parent = match &config.parent {
+            Some(parent) => Some(self.resolve_domain(&config.name, parent)?),
+            None => None,
+        };
+        let domain = FaultDomain::try_new(&config.name, config.kind, config.sla, parent)
+            .map_err(|err| invalid(&config.name, err))?;
+
+        self.leave();
+        self.domains.insert(config.name.clone(), domain.clone());
+        Ok(domain)
+    }
+
+    fn resolve_domains(
+        &mut self,
+        from: &str,
+        names: &[String],
+    ) -> Result<Vec<Arc<FaultDomain>>, ConfigError> {
+        names
+            .iter()
+            .map(|name| self.resolve_domain(from, name))
+            .collect()
+    }
+
+    fn resolve_program(&mut self, config: &'a ProgramConfig) -> Result<Arc<Service>, ConfigError> {
+        if let Some(service) = self.services.get(&config.name) {
+            return Ok(service.clone());
+        }
+        self.enter(&config.name)?;
+
+        let infra = self.resolve_infra(&config.name, &config.infra)?;
+        let mut dependencies = Vec::new();
+        for dependency in config.dependencies.iter() {
+            let connection = self.resolve_connection(&config.name, &dependency.via)?;
+            let dependency = self.resolve_dependency(&config.name, &dependency.to)?;
+            dependencies.push((connection, dependency));
+        }
+        let program = Service::program(&config.name, infra, dependencies);
+
+        self.leave();
+        self.services.insert(config.name.clone(), program.clone());
+        Ok(program)
+    }
+}
+
+fn invalid(name: &str, source: SlacError) -> ConfigError {
+    ConfigError::Invalid {
+        name: name.to_string(),
+        source,
+    }
+}
+
+fn credit_policy(
+    name: &str,
+    tiers: &[CreditTierConfig],
+) -> Result<Option<CreditPolicy>, ConfigError> {
+    if tiers.is_empty() {
+        return Ok(None);
+    }
+
+    let tiers = tiers
+        .iter()
+        .map(|tier| CreditTier::new(tier.below, tier.credit))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| invalid(name, err))?;
+    Ok(Some(CreditPolicy::new(tiers)))
+}
+
+impl Config {
+    fn definitions(&self) -> impl Iterator<Item = Definition<'_>> {
+        let services = self.services.iter().map(Definition::Service);
+        let groups = self.groups.iter().map(Definition::Group);
+        let infras = self.infras.iter().map(Definition::Infra);
+        let connections = self.connections.iter().map(Definition::Connection);
+        let programs = self.programs.iter().map(Definition::Program);
+        let domains = self.fault_domains.iter().map(Definition::FaultDomain);
+
+        services
+            .chain(groups)
+            .chain(infras)
+            .chain(connections)
+            .chain(programs)
+            .chain(domains)
+    }
+
+    // build resolves all named references and constructs the `sla` model
+    pub fn build(&self) -> Result<Topology, ConfigError> {
+        let mut definitions = HashMap::new();
+        for definition in self.definitions() {
+            if definitions.insert(definition.name(), definition).is_some() {
+                return Err(ConfigError::DuplicateName(definition.name().to_string()));
+            }
+        }
+
+        let mut resolver = Resolver {
+            definitions,
+            services: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            infras: BTreeMap::new(),
+            connections: BTreeMap::new(),
+            domains: BTreeMap::new(),
+            resolving: Vec::new(),
+        };
+        for definition in self.definitions() {
+            match definition {
+                Definition::Service(service) => {
+                    resolver.resolve_service(service)?;
+                }
+                Definition::Group(group) => {
+                    resolver.resolve_group(group)?;
+                }
+                Definition::Program(program) => {
+                    resolver.resolve_program(program)?;
+                }
+                // infras, connections and fault domains are resolved when
+                // they are used
+                Definition::Infra(_) | Definition::Connection(_) | Definition::FaultDomain(_) => {}
+            }
+        }
+
+        let targets = match &self.target {
+            Some(target) => {
+                if !resolver.services.contains_key(target) {
+                    return Err(ConfigError::UnknownTarget(target.clone()));
+                }
+                vec![target.clone()]
+            }
+            None => {
+                // every service or program which is not referred by others is
+                // a target
+                let mut referred = HashSet::new();
+                for service in self.services.iter() {
+                    referred.extend(service.dependencies.iter().map(String::as_str));
+                }
+                for group in self.groups.iter() {
+                    referred.extend(group.members.iter().map(String::as_str));
+                }
+                for program in self.programs.iter() {
+                    referred.extend(program.dependencies.iter().map(|dep| dep.to.as_str()));
+                }
+
+                self.definitions()
+                    .filter(|definition| {
+                        matches!(definition, Definition::Service(_) | Definition::Program(_))
+                    })
+                    .map(|definition| definition.name())
+                    .filter(|name| !referred.contains(name))
+                    .map(str::to_string)
+                    .collect()
+            }
+        };
+
+        // the targets are validated as a whole, as the same name may be used
+        // with different slas in different parts of the model
+        for target in targets.iter() {
+            resolver.services[target]
+                .validate()
+                .map_err(|err| invalid(target, err))?;
+        }
+
+        Ok(Topology {
+            services: resolver.services,
+            groups: resolver.groups,
+            targets,
+            credits: credit_policy("credits", &self.credits)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use float_cmp::approx_eq;
+
+    use crate::{
+        calculate::{AtomRegistry, DumpTerm},
+        config::{parse_duration, parse_sla, Config, ConfigError, Format},
+        error::SlacError,
+    };
+
+    #[test]
+    fn test_build() {
+        let yaml = r#"
+services:
+  - name: infra_a
+    sla: 0.9
+  - name: infra_b
+    sla: 0.8
+  - name: svc_a
+    dependencies: [infra_a]
+  - name: svc_b
+    dependencies: [infra_b]
+  - name: frontend
+    dependencies: [infra_a, replicas]
+groups:
+  - name: replicas
+    members: [svc_a, svc_b]
+    quorum: 1
+"#;
+        let toml = r#"
+[[services]]
+name = "infra_a"
+sla = "90%"
+
+[[services]]
+name = "infra_b"
+sla = 0.8
+
+[[services]]
+name = "svc_a"
+dependencies = ["infra_a"]
+
+[[services]]
+name = "svc_b"
+dependencies = ["infra_b"]
+
+[[services]]
+name = "frontend"
+dependencies = ["infra_a", "replicas"]
+
+[[groups]]
+name = "replicas"
+members = ["svc_a", "svc_b"]
+quorum = 1
+"#;
+
+        assert_eq!(parse_sla("99.95%"), Some(0.9995));
+        assert_eq!(parse_sla("0.9995"), Some(0.9995));
+
+        for (content, format) in [(yaml, Format::Yaml), (toml, Format::Toml)] {
+            let topology = Config::parse(content, format).unwrap().build().unwrap();
+            let targets: Vec<_> = topology.targets().collect();
+            assert_eq!(targets.len(), 1);
+            assert_eq!(targets[0].0, "frontend");
+
+            let mut registry = AtomRegistry::default();
+            let got = targets[0].1.dump_term(&mut registry).calc();
+            assert!(approx_eq!(f64, got, 0.9, epsilon = 0.0000001f64));
+        }
+    }
+
+    #[test]
+    fn test_build_program() {
+        let yaml = r#"
+services:
+  - name: database
+    sla: 0.5
+infras:
+  - name: infra_a
+    sla: 0.9
+connections:
+  - name: connection_a
+    sla: 0.8
+programs:
+  - name: api
+    infra: infra_a
+    dependencies:
+      - to: database
+        via: connection_a
+"#;
+        let topology = Config::parse(yaml, Format::Yaml).unwrap().build().unwrap();
+        let targets: Vec<_> = topology.targets().collect();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].0, "api");
+
+        let mut registry = AtomRegistry::default();
+        let got = targets[0].1.dump_term(&mut registry).calc();
+        assert!(approx_eq!(
+            f64,
+            got,
+            0.9 * 0.8 * 0.5,
+            epsilon = 0.0000001f64
+        ));
+
+        let yaml = r#"
+infras:
+  - name: infra_a
+    sla: 0.9
+programs:
+  - name: api
+    infra: infra_a
+groups:
+  - name: group_a
+    members: [api, infra_a]
+    quorum: 1
+"#;
+        let config = Config::parse(yaml, Format::Yaml).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::UnexpectedReference { .. })
+        ));
+    }
+
+    #[test]
+    fn test_build_fault_domain() {
+        let yaml = r#"
+fault_domains:
+  - name: us-east-1
+    kind: region
+    sla: 0.999
+  - name: us-east-1a
+    kind: zone
+    sla: 0.99
+    parent: us-east-1
+infras:
+  - name: infra_a
+    sla: 0.9
+    domains: [us-east-1a]
+programs:
+  - name: api
+    infra: infra_a
+services:
+  - name: database
+    sla: 0.8
+    domains: [us-east-1a]
+  - name: app
+    dependencies: [api, database]
+"#;
+        let topology = Config::parse(yaml, Format::Yaml).unwrap().build().unwrap();
+        let targets: Vec<_> = topology.targets().collect();
+        assert_eq!(targets.len(), 1);
+
+        // the zone and region are only counted once
+        let mut registry = AtomRegistry::default();
+        let got = targets[0].1.dump_term(&mut registry).calc();
+        assert!(approx_eq!(
+            f64,
+            got,
+            0.999 * 0.99 * 0.9 * 0.8,
+            epsilon = 0.0000001f64
+        ));
+
+        let yaml = r#"
+fault_domains:
+  - name: zone
+    kind: zone
+    sla: 0.99
+services:
+  - name: database
+    sla: 0.8
+  - name: app
+    dependencies: [database]
+    domains: [zone]
+"#;
+        let config = Config::parse(yaml, Format::Yaml).unwrap();
+        match config.build() {
+            Err(err @ ConfigError::UnexpectedDomains(_)) => assert_eq!(
+                err.to_string(),
+                "service app has fault domains, but only a service with an sla or a failure model could be placed in fault domains"
+            ),
+            _ => panic!("the domains should be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_build_failure_model() {
+        assert_eq!(parse_duration("30d"), Some(Duration::from_secs(30 * 86400)));
+        assert_eq!(parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("30"), None);
+        assert_eq!(parse_duration("d"), None);
+
+        let toml = r#"
+[[services]]
+name = "database"
+mtbf = "999h"
+mttr = "1h"
+
+[[services]]
+name = "disk"
+failure_rate = 0.001
+
+[[services]]
+name = "app"
+dependencies = ["database", "disk"]
+"#;
+        let topology = Config::parse(toml, Format::Toml).unwrap().build().unwrap();
+        let (_, app) = topology.targets().next().unwrap();
+
+        let mut registry = AtomRegistry::default();
+        let got = app.dump_term(&mut registry).calc();
+        let expected = 0.999 * (-0.001f64 * 720.0).exp();
+        assert!(approx_eq!(f64, got, expected, epsilon = 0.0000001f64));
+
+        let json = r#"{"services": [{"name": "a", "sla": 0.9, "failure_rate": 0.001}]}"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::AmbiguousService(_))
+        ));
+
+        let json = r#"{"services": [{"name": "a", "mtbf": "999h"}]}"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::AmbiguousService(_))
+        ));
+    }
+
+    #[test]
+    fn test_build_credits() {
+        let yaml = r#"
+credits:
+  - below: 99.9%
+    credit: 10%
+  - below: 99%
+    credit: 25%
+services:
+  - name: compute
+    sla: 99.99%
+    credits:
+      - below: 99.99%
+        credit: 10%
+  - name: storage
+    sla: 99.9%
+  - name: app
+    dependencies: [compute, storage]
+"#;
+        let topology = Config::parse(yaml, Format::Yaml).unwrap().build().unwrap();
+        let credits = topology.credits().unwrap();
+        assert_eq!(credits.credit(0.995), 0.1);
+        assert_eq!(credits.credit(0.95), 0.25);
+
+        let (_, app) = topology.targets().next().unwrap();
+        let policies = app.credit_policies();
+        assert_eq!(policies.len(), 1);
+        assert_eq!(policies[0].0, "compute");
+        assert_eq!(policies[0].1, 0.9999);
+        assert_eq!(policies[0].2.credit(0.999), 0.1);
+
+        let yaml = r#"
+services:
+  - name: storage
+    sla: 99.9%
+  - name: app
+    dependencies: [storage]
+    credits:
+      - below: 99.9%
+        credit: 10%
+"#;
+        let config = Config::parse(yaml, Format::Yaml).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::UnexpectedCredits(_))
+        ));
+    }
+
+    #[test]
+    fn test_build_error() {
+        let json = r#"{"services": [{"name": "a", "dependencies": ["b"]}]}"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::UnknownReference { .. })
+        ));
+
+        let json = r#"{
+            "services": [
+                {"name": "a", "sla": 0.9},
+                {"name": "b", "dependencies": ["a", "c"]},
+                {"name": "c", "dependencies": ["group"]},
+                {"name": "d", "dependencies": ["a"]}
+            ],
+            "groups": [
+                {"name": "group", "members": ["d", "b"], "quorum": 1}
+            ]
+        }"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        match config.build() {
+            Err(ConfigError::Cycle(path)) => assert_eq!(path, vec!["b", "c", "group", "b"]),
+            _ => panic!("the cycle should be detected"),
+        }
+
+        let json = r#"{
+            "services": [{"name": "a", "sla": "99.9%"}, {"name": "b", "sla": 0.9}],
+            "groups": [{"name": "group", "members": ["a", "b"], "quorum": 3}]
+        }"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        match config.build() {
+            Err(err @ ConfigError::Invalid { .. }) => assert_eq!(
+                err.to_string(),
+                "invalid group: quorum of a group with 2 members should be in [1, 2], got 3"
+            ),
+            _ => panic!("the quorum should be rejected"),
+        }
+
+        let json = r#"{
+            "services": [{"name": "a", "sla": 0.9}, {"name": "b", "sla": 0.99}],
+            "groups": [{"name": "group", "members": ["a", "b"], "quorum": 1, "beta": "10%"}]
+        }"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::Invalid {
+                source: SlacError::NonIdenticalMember { .. },
+                ..
+            })
+        ));
+
+        let json = r#"{
+            "services": [
+                {"name": "a", "sla": 0.9},
+                {"name": "b", "sla": 0.9},
+                {"name": "c", "dependencies": ["a", "group"]}
+            ],
+            "groups": [{"name": "group", "members": ["a", "b"], "quorum": 1, "beta": "10%"}]
+        }"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::Invalid {
+                name,
+                source: SlacError::SharedCommonCauseMember { .. },
+            }) if name == "c"
+        ));
+
+        let json = r#"{
+            "services": [
+                {"name": "a", "sla": 0.9},
+                {"name": "b", "sla": 0.9},
+                {"name": "a/independent", "sla": 0.5},
+                {"name": "c", "dependencies": ["a/independent", "group"]}
+            ],
+            "groups": [{"name": "group", "members": ["a", "b"], "quorum": 1, "beta": "10%"}]
+        }"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::Invalid {
+                name,
+                source: SlacError::ConflictingAtom { .. },
+            }) if name == "c"
+        ));
+
+        let json = r#"{"services": [{"name": "a"}]}"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::AmbiguousService(name)) if name == "a"
+        ));
+
+        let json = r#"{"services": [{"name": "a", "sla": 1.5}]}"#;
+        let config = Config::parse(json, Format::Json).unwrap();
+        assert!(matches!(
+            config.build(),
+            Err(ConfigError::Invalid {
+                source: SlacError::InvalidSla { .. },
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::fmt;
+
+// SlacError is an invalid input of the sla model or the calculation
+#[derive(Debug, Clone, PartialEq)]
+pub enum SlacError {
+    // the sla (or probability) is not in [0, 1], or is NaN
+    InvalidSla {
+        name: String,
+        sla: f64,
+    },
+    // the quorum of a group is not in [1, members]
+    InvalidQuorum {
+        quorum: usize,
+        members: usize,
+    },
+    // the mtbf of a repairable component is zero, or the failure rate is
+    // negative or NaN
+    InvalidFailureModel {
+        name: String,
+    },
+    // the beta factor of a common cause group is not in [0, 1]
+    InvalidBeta {
+        beta: f64,
+    },
+    // a member of a common cause group is not a service with predefined sla,
+    // or its sla is different from the others
+    NonIdenticalMember {
+        name: String,
+    },
+    // the conditional probability table should have an entry for every
+    // assignment of the parents
+    InvalidTable {
+        name: String,
+        expected: usize,
+        got: usize,
+    },
+    // the threshold or the credit of a credit tier is not in [0, 1]
+    InvalidCreditTier {
+        below: f64,
+        credit: f64,
+    },
+    // the same name is registered with different probabilities
+    ConflictingAtom {
+        name: String,
+        existing: f64,
+        requested: f64,
+    },
+    // a service depends on nothing, so its availability is meaningless
+    EmptyDependencies,
+    // a member of a common cause group is also referred outside of the group,
+    // where its failures wouldn't be shared with the common cause
+    SharedCommonCauseMember {
+        name: String,
+    },
+    // the target of an error budget is 100%, which leaves no budget
+    InvalidTarget {
+        target: f64,
+    },
+}
+
+impl fmt::Display for SlacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlacError::InvalidSla { name, sla } => {
+                write!(f, "sla of {} should be in [0, 1], got {}", name, sla)
+            }
+            SlacError::InvalidQuorum { quorum, members } => write!(
+                f,
+                "quorum of a group with {} members should be in [1, {}], got {}",
+                members, members, quorum
+            ),
+            SlacError::InvalidFailureModel { name } => write!(
+                f,
+                "{} should have a positive mtbf, or a non-negative failure rate",
+                name
+            ),
+            SlacError::InvalidBeta { beta } => {
+                write!(f, "beta factor should be in [0, 1], got {}", beta)
+            }
+            SlacError::NonIdenticalMember { name } => write!(
+                f,
+                "{} should be a service with the same sla as other members of the common cause group",
+                name
+            ),
+            SlacError::InvalidTable {
+                name,
+                expected,
+                got,
+            } => write!(
+                f,
+                "conditional probability table of {} should have {} entries, got {}",
+                name, expected, got
+            ),
+            SlacError::InvalidCreditTier { below, credit } => write!(
+                f,
+                "credit tier should have a threshold and a credit in [0, 1], got {} below {}",
+                credit, below
+            ),
+            SlacError::ConflictingAtom {
+                name,
+                existing,
+                requested,
+            } => write!(
+                f,
+                "{} is defined with sla {}, but also with sla {}",
+                name, existing, requested
+            ),
+            SlacError::EmptyDependencies => {
+                write!(f, "a service should depend on at least one service or group")
+            }
+            SlacError::SharedCommonCauseMember { name } => write!(
+                f,
+                "{} is a member of a common cause group, and shouldn't be referred elsewhere",
+                name
+            ),
+            SlacError::InvalidTarget { target } => {
+                write!(f, "target should be lower than 1 to leave an error budget, got {}", target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SlacError {}
+
+// ParseError is an invalid textual expression of a term
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    // the input doesn't match the syntax, `expected` describes what should be
+    // at the byte offset
+    UnexpectedToken {
+        position: usize,
+        expected: &'static str,
+    },
+    // the atom is not registered in the registry
+    UnknownAtom {
+        position: usize,
+        name: String,
+    },
+    // the threshold of `atleast` is larger than the number of its terms
+    InvalidThreshold {
+        position: usize,
+        k: usize,
+        terms: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedToken { position, expected } => {
+                write!(f, "expected {} at position {}", expected, position)
+            }
+            ParseError::UnknownAtom { position, name } => write!(
+                f,
+                "atom {} at position {} is not registered",
+                name, position
+            ),
+            ParseError::InvalidThreshold { position, k, terms } => write!(
+                f,
+                "threshold at position {} should be in [0, {}], got {}",
+                position, terms, k
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub(crate) fn check_sla(name: &str, sla: f64) -> Result<(), SlacError> {
+    if (0.0..=1.0).contains(&sla) {
+        Ok(())
+    } else {
+        Err(SlacError::InvalidSla {
+            name: name.to_string(),
+            sla,
+        })
+    }
+}
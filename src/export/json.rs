@@ -0,0 +1,116 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Write},
+};
+
+use serde_json::{json, Map, Value};
+
+use super::{Export, Node, Walk};
+
+// render_json writes the graph as a json tree:
+//
+// {
+//   "graph": "<graph id>",
+//   "roots": [<node>, ...]
+// }
+//
+// where a node is
+//
+// {
+//   "id": "<node id>",
+//   "label": "<label, with lines separated by \n>",
+//   "edge": "<label of the edge from the parent, omitted if empty>",
+//   "color": "<fill color, omitted if not filled>",
+//   "probability" | "contribution" | "availability": <number>,
+//   "children": [<node>, ...]
+// }
+//
+// A node shared by many parents, e.g. a merged atom or a shared service, is
+// written in full at its first appearance, and only as `{"id", "edge"}` at
+// the others, so the tree is not duplicated.
+pub fn render_json<'a, N, E, G, W>(g: &'a G, w: &mut W) -> io::Result<()>
+where
+    N: Clone + 'a,
+    E: Clone + 'a,
+    G: Export<'a, N, E>,
+    W: Write,
+{
+    let walk = Walk::new(g);
+
+    // the roots are the nodes without any parent
+    let targets: HashSet<&str> = walk
+        .children
+        .values()
+        .flatten()
+        .map(|(target, _)| target.as_str())
+        .collect();
+    let nodes: HashMap<&str, &Node> = walk
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), node))
+        .collect();
+    let mut written = HashSet::new();
+    let roots: Vec<Value> = walk
+        .nodes
+        .iter()
+        .filter(|node| !targets.contains(node.id.as_str()))
+        .map(|node| tree(&walk, &nodes, &node.id, "", &mut written))
+        .collect();
+
+    let value = json!({
+        "graph": walk.graph_id,
+        "roots": roots,
+    });
+    serde_json::to_writer_pretty(&mut *w, &value)?;
+    writeln!(w)
+}
+
+fn tree<'w>(
+    walk: &'w Walk,
+    nodes: &HashMap<&str, &Node>,
+    id: &'w str,
+    edge: &str,
+    written: &mut HashSet<&'w str>,
+) -> Value {
+    let mut object = Map::new();
+    object.insert("id".to_string(), json!(id));
+    if !edge.is_empty() {
+        object.insert("edge".to_string(), json!(edge));
+    }
+    if !written.insert(id) {
+        return Value::Object(object);
+    }
+
+    if let Some(node) = nodes.get(id) {
+        object.insert("label".to_string(), json!(node.label));
+        if let Some(color) = &node.color {
+            object.insert("color".to_string(), json!(color));
+        }
+        for (name, value) in node.values.iter() {
+            object.insert(name.to_string(), json!(value));
+        }
+    }
+    let children: Vec<Value> = walk
+        .children(id)
+        .iter()
+        .map(|(target, label)| tree(walk, nodes, target, label, written))
+        .collect();
+    object.insert("children".to_string(), Value::Array(children));
+
+    Value::Object(object)
+}
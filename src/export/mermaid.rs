@@ -0,0 +1,61 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::io::{self, Write};
+
+use super::{Export, Walk};
+
+// render_mermaid writes the graph as a mermaid flowchart, which is rendered by
+// most markdown viewers. A node filled in dot is filled with the same color.
+pub fn render_mermaid<'a, N, E, G, W>(g: &'a G, w: &mut W) -> io::Result<()>
+where
+    N: Clone + 'a,
+    E: Clone + 'a,
+    G: Export<'a, N, E>,
+    W: Write,
+{
+    let walk = Walk::new(g);
+
+    writeln!(w, "flowchart TD")?;
+    for node in walk.nodes.iter() {
+        let label = escape(&node.label);
+        match node.shape.as_deref() {
+            Some("hexagon") => writeln!(w, "    {}{{{{\"{}\"}}}}", node.id, label)?,
+            Some("ellipse") => writeln!(w, "    {}([\"{}\"])", node.id, label)?,
+            _ => writeln!(w, "    {}[\"{}\"]", node.id, label)?,
+        }
+    }
+    for node in walk.nodes.iter() {
+        for (target, label) in walk.children(&node.id) {
+            match label.is_empty() {
+                true => writeln!(w, "    {} --> {}", node.id, target)?,
+                false => writeln!(w, "    {} -->|\"{}\"| {}", node.id, escape(label), target)?,
+            }
+        }
+    }
+    for node in walk.nodes.iter() {
+        if let Some(color) = &node.color {
+            writeln!(w, "    style {} fill:{}", node.id, color)?;
+        }
+    }
+
+    Ok(())
+}
+
+// escape replaces the characters which are not allowed in a quoted mermaid
+// label with entity codes
+fn escape(label: &str) -> String {
+    label.replace('"', "#quot;").replace('\n', "<br/>")
+}
@@ -0,0 +1,223 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+// The exporters draw any graph which can be rendered by `dot::render`, e.g. a
+// `Term`, a `TermGraph` or a `ServiceGraph`, so they share the same walk of
+// the nodes and edges, and the same labels.
+
+mod json;
+mod mermaid;
+mod report;
+
+pub use json::render_json;
+pub use mermaid::render_mermaid;
+pub use report::{Report, ReportOptions};
+
+use std::collections::HashMap;
+
+use crate::{
+    calculate::{Term, TermEdge, TermGraph, TermNode},
+    sla::{ServiceEdge, ServiceGraph},
+};
+
+// Export is a graph which can be rendered by `dot::render`, with the numbers of
+// every node which are exported to json in addition to its label
+pub trait Export<'a, N: Clone, E: Clone>:
+    dot::Labeller<'a, N, E> + dot::GraphWalk<'a, N, E>
+{
+    fn node_values(&'a self, _n: &N) -> Vec<(&'static str, f64)> {
+        Vec::new()
+    }
+}
+
+impl<'a> Export<'a, TermNode, TermEdge> for Term {}
+
+impl<'a> Export<'a, TermNode, TermEdge> for TermGraph {
+    fn node_values(&'a self, n: &TermNode) -> Vec<(&'static str, f64)> {
+        let mut values = Vec::new();
+        if let Some(probability) = n.probability() {
+            values.push(("probability", probability));
+        }
+        if let Some(contribution) = n.contribution() {
+            values.push(("contribution", contribution));
+        }
+        values
+    }
+}
+
+impl<'a> Export<'a, usize, ServiceEdge> for ServiceGraph {
+    fn node_values(&'a self, n: &usize) -> Vec<(&'static str, f64)> {
+        vec![("availability", self.node(*n).availability())]
+    }
+}
+
+struct Node {
+    id: String,
+    label: String,
+    shape: Option<String>,
+    color: Option<String>,
+    values: Vec<(&'static str, f64)>,
+}
+
+// Walk is the nodes and edges of a graph, with the labels resolved to plain
+// text
+struct Walk {
+    graph_id: String,
+    nodes: Vec<Node>,
+    // the targets of the edges from every node, and the labels of the edges
+    children: HashMap<String, Vec<(String, String)>>,
+}
+
+impl Walk {
+    fn new<'a, N, E, G>(g: &'a G) -> Walk
+    where
+        N: Clone + 'a,
+        E: Clone + 'a,
+        G: Export<'a, N, E>,
+    {
+        let nodes = g
+            .nodes()
+            .iter()
+            .map(|n| Node {
+                id: g.node_id(n).as_slice().to_string(),
+                label: text(g.node_label(n)),
+                shape: g.node_shape(n).map(text),
+                color: g.node_color(n).map(text),
+                values: g.node_values(n),
+            })
+            .collect();
+
+        let mut children: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for e in g.edges().iter() {
+            children
+                .entry(g.node_id(&g.source(e)).as_slice().to_string())
+                .or_default()
+                .push((
+                    g.node_id(&g.target(e)).as_slice().to_string(),
+                    text(g.edge_label(e)),
+                ));
+        }
+
+        Walk {
+            graph_id: g.graph_id().as_slice().to_string(),
+            nodes,
+            children,
+        }
+    }
+
+    fn children(&self, id: &str) -> &[(String, String)] {
+        self.children.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+// text returns the content of a label, in which a new line is `\n`
+fn text(label: dot::LabelText) -> String {
+    match label {
+        dot::LabelText::LabelStr(text) => text.into_owned(),
+        dot::LabelText::EscStr(text) => text.replace("\\n", "\n"),
+        dot::LabelText::HtmlStr(text) => text.into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::Value;
+
+    use crate::{
+        calculate::{AtomRegistry, RenderOptions, Term, TermGraph, UnaryOp},
+        sla::{Dependency, Group, Service, ServiceGraph},
+    };
+
+    use super::{render_json, render_mermaid};
+
+    #[test]
+    fn test_export_term() {
+        let mut registry = AtomRegistry::default();
+        let a = registry.new_atom("a".to_owned(), 0.9);
+        let b = registry.new_atom("b \"quoted\"".to_owned(), 0.9);
+        let term = Term::union(vec![
+            Term::intersect(vec![Term::atom(a.clone()), Term::atom(b)]),
+            Term::Unary {
+                atom: Arc::clone(&a),
+                op: UnaryOp::Not,
+            },
+        ]);
+        let options = RenderOptions {
+            merge_atoms: true,
+            annotate: true,
+            heat_map: true,
+            ..Default::default()
+        };
+        let graph = TermGraph::with_options(&term, options);
+
+        let mut output = Vec::new();
+        render_mermaid(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("flowchart TD\n"));
+        assert!(output.contains("    a1[\"b #quot;quoted#quot;<br/>90.00%\"]\n"));
+        assert!(output.contains("    n_1 --> a0\n"));
+        assert!(output.contains("    style n fill:#ff0000\n"));
+
+        let mut output = Vec::new();
+        render_json(&graph, &mut output).unwrap();
+        let value: Value = serde_json::from_slice(&output).unwrap();
+        let root = &value["roots"][0];
+        assert_eq!(value["roots"].as_array().unwrap().len(), 1);
+        assert_eq!(root["id"], "n");
+        assert_eq!(root["probability"].as_f64(), Some(term.calc()));
+        assert_eq!(root["children"][0]["children"][0]["label"], "a\n90.00%");
+        // the merged atom is written once, and referred by its id later
+        assert_eq!(
+            root["children"][1]["children"][0],
+            serde_json::json!({ "id": "a0" })
+        );
+    }
+
+    #[test]
+    fn test_export_service_graph() {
+        let database = Service::known_sla("database", 0.999);
+        let members = (0..2)
+            .map(|index| {
+                Service::dependencies(vec![
+                    Dependency::Service(database.clone()),
+                    Dependency::Service(Service::known_sla(format!("replica_{}", index), 0.99)),
+                ])
+            })
+            .collect();
+        let group = Group::new(members, 1);
+
+        let mut graph = ServiceGraph::default();
+        graph.name_group(&group, "replicas");
+        graph.add(&Service::dependencies(vec![Dependency::Group(group)]));
+
+        let mut output = Vec::new();
+        render_mermaid(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("    n1{{\"replicas<br/>1 of 2<br/>"));
+
+        let mut output = Vec::new();
+        render_json(&graph, &mut output).unwrap();
+        let value: Value = serde_json::from_slice(&output).unwrap();
+        let replicas = &value["roots"][0]["children"][0];
+        assert_eq!(value["graph"], "services");
+        assert!(replicas["availability"].as_f64().unwrap() > 0.998);
+        assert_eq!(
+            replicas["children"][1]["children"][0],
+            serde_json::json!({ "id": "n3" })
+        );
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::io::{self, Write};
+
+use crate::{
+    calculate::{format_duration, Availability, CutSet, Importance, Term},
+    sla::ServiceGraph,
+};
+
+use super::render_json;
+
+// ReportOptions controls how many cut sets are listed for every service
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    // skip the cut sets with more components than this
+    pub max_order: Option<usize>,
+    // list at most this number of cut sets
+    pub limit: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        // enumerating the cut sets of a large order is exponential, and they
+        // are hardly relevant in practice
+        ReportOptions {
+            max_order: Some(4),
+            limit: 20,
+        }
+    }
+}
+
+struct ServiceReport {
+    name: String,
+    availability: Availability,
+    cut_sets: Vec<CutSet>,
+    importance: Vec<Importance>,
+}
+
+// Report is a single html file for an architecture proposal, with the input
+// topology, the availability of every service, the minimal cut sets, the
+// importance of every component, and an interactive dependency diagram. It
+// doesn't load anything from the network, so it could be archived or mailed.
+pub struct Report {
+    title: String,
+    source: String,
+    options: ReportOptions,
+
+    // the json tree of the dependency graph, see `render_json`
+    graph: Option<String>,
+    services: Vec<ServiceReport>,
+}
+
+impl Report {
+    pub fn new<T: AsRef<str>, S: AsRef<str>>(
+        title: T,
+        source: S,
+        options: ReportOptions,
+    ) -> Report {
+        Report {
+            title: title.as_ref().to_string(),
+            source: source.as_ref().to_string(),
+            options,
+            graph: None,
+            services: Vec::new(),
+        }
+    }
+
+    // add analyzes the term of a service
+    pub fn add<S: AsRef<str>>(&mut self, name: S, term: &Term) {
+        let mut cut_sets = term.minimal_cut_sets(self.options.max_order);
+        cut_sets.truncate(self.options.limit);
+
+        self.services.push(ServiceReport {
+            name: name.as_ref().to_string(),
+            availability: term.availability(),
+            cut_sets,
+            importance: term.importance(),
+        });
+    }
+
+    // with_graph embeds the dependency graph of the services as the diagram
+    pub fn with_graph(&mut self, graph: &ServiceGraph) -> io::Result<()> {
+        let mut json = Vec::new();
+        render_json(graph, &mut json)?;
+        self.graph = Some(String::from_utf8_lossy(&json).into_owned());
+        Ok(())
+    }
+
+    pub fn render<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let title = escape(&self.title);
+        writeln!(w, "<!DOCTYPE html>")?;
+        writeln!(w, "<html>\n<head>\n<meta charset=\"utf-8\">")?;
+        writeln!(w, "<title>SLA report: {}</title>", title)?;
+        writeln!(w, "<style>{}</style>\n</head>\n<body>", STYLE)?;
+        writeln!(w, "<h1>SLA report: {}</h1>", title)?;
+
+        writeln!(w, "<h2>Availability</h2>\n<table>")?;
+        writeln!(
+            w,
+            "<tr><th>service</th><th>availability</th><th>nines</th>\
+             <th>downtime per month</th><th>downtime per year</th></tr>"
+        )?;
+        for service in self.services.iter() {
+            writeln!(
+                w,
+                "<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td></tr>",
+                escape(&service.name),
+                service.availability.percent(),
+                service.availability.nines(),
+                format_duration(service.availability.downtime_per_month()),
+                format_duration(service.availability.downtime_per_year())
+            )?;
+        }
+        writeln!(w, "</table>")?;
+
+        if let Some(graph) = &self.graph {
+            writeln!(w, "<h2>Dependencies</h2>")?;
+            writeln!(
+                w,
+                "<p>Click a node to highlight everything it depends on.</p>"
+            )?;
+            writeln!(
+                w,
+                "<svg id=\"diagram\" xmlns=\"http://www.w3.org/2000/svg\"></svg>"
+            )?;
+            // a json string could contain `</script>`, which would end the
+            // script element, and `<\/` is the same string in json
+            writeln!(
+                w,
+                "<script type=\"application/json\" id=\"graph\">{}</script>",
+                graph.trim_end().replace("</", "<\\/")
+            )?;
+            writeln!(w, "<script>{}</script>", SCRIPT)?;
+        }
+
+        for service in self.services.iter() {
+            self.render_service(service, w)?;
+        }
+
+        writeln!(w, "<h2>Topology</h2>")?;
+        writeln!(w, "<pre>{}</pre>", escape(&self.source))?;
+        writeln!(w, "</body>\n</html>")
+    }
+
+    fn render_service<W: Write>(&self, service: &ServiceReport, w: &mut W) -> io::Result<()> {
+        writeln!(w, "<h2>{}</h2>", escape(&service.name))?;
+
+        writeln!(w, "<h3>Minimal cut sets</h3>")?;
+        if let Some(max_order) = self.options.max_order {
+            writeln!(w, "<p>With at most {} components.</p>", max_order)?;
+        }
+        writeln!(w, "<table>")?;
+        writeln!(w, "<tr><th>probability</th><th>components</th></tr>")?;
+        for cut_set in service.cut_sets.iter() {
+            let atoms: Vec<String> = cut_set
+                .atoms()
+                .iter()
+                .map(|atom| escape(atom.name()))
+                .collect();
+            writeln!(
+                w,
+                "<tr><td>{:.4e}</td><td>{}</td></tr>",
+                cut_set.probability(),
+                atoms.join(", ")
+            )?;
+        }
+        writeln!(w, "</table>")?;
+
+        writeln!(w, "<h3>Importance</h3>\n<table>")?;
+        writeln!(
+            w,
+            "<tr><th>component</th><th>birnbaum</th><th>fussell-vesely</th>\
+             <th>raw</th><th>rrw</th></tr>"
+        )?;
+        for item in service.importance.iter() {
+            writeln!(
+                w,
+                "<tr><td>{}</td><td>{:.4e}</td><td>{:.4e}</td><td>{:.4}</td><td>{:.4}</td></tr>",
+                escape(item.atom().name()),
+                item.birnbaum(),
+                item.fussell_vesely(),
+                item.risk_achievement_worth(),
+                item.risk_reduction_worth()
+            )?;
+        }
+        writeln!(w, "</table>")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }
+th { background: #f4f4f4; }
+pre { background: #f8f8f8; padding: 1em; overflow: auto; }
+#diagram { display: block; max-width: 100%; height: auto; }
+#diagram rect { fill: #fff; stroke: #555; }
+#diagram path { fill: none; stroke: #999; }
+#diagram text { font-size: 12px; text-anchor: middle; }
+#diagram .edge { fill: #888; font-size: 10px; }
+#diagram .availability { fill: #06c; }
+#diagram g { cursor: pointer; }
+#diagram .dim { opacity: 0.2; }
+";
+
+// the script draws the json tree of the graph as an svg diagram. Every node
+// is placed in the layer below all of its parents, and the nodes in a layer
+// are ordered by the positions of their parents, to avoid crossing edges.
+const SCRIPT: &str = r#"
+const graph = JSON.parse(document.getElementById('graph').textContent);
+const svg = document.getElementById('diagram');
+const NS = 'http://www.w3.org/2000/svg';
+
+// a shared node is written in full only at its first appearance
+const nodes = new Map();
+const edges = [];
+function collect(node) {
+  if (node.label !== undefined) {
+    nodes.set(node.id, { id: node.id, label: node.label, color: node.color, layer: 0, order: nodes.size });
+    node.children.forEach((child) => {
+      edges.push({ from: node.id, to: child.id, label: child.edge || '' });
+      collect(child);
+    });
+  }
+}
+graph.roots.forEach(collect);
+
+// the graph is acyclic, so the longest paths are found in at most |nodes| passes
+for (let changed = true; changed;) {
+  changed = false;
+  edges.forEach((edge) => {
+    const from = nodes.get(edge.from), to = nodes.get(edge.to);
+    if (to.layer <= from.layer) {
+      to.layer = from.layer + 1;
+      changed = true;
+    }
+  });
+}
+
+const layers = [];
+nodes.forEach((node) => {
+  const lines = node.label.split('\n');
+  node.lines = lines;
+  node.width = Math.max(...lines.map((line) => line.length)) * 7 + 16;
+  node.height = lines.length * 15 + 8;
+  (layers[node.layer] = layers[node.layer] || []).push(node);
+});
+
+const GAP = 24, LAYER = 80;
+let width = 0, y = GAP;
+layers.forEach((layer, index) => {
+  if (index > 0) {
+    const center = (node) => {
+      const parents = edges.filter((edge) => edge.to === node.id).map((edge) => nodes.get(edge.from));
+      return parents.reduce((sum, parent) => sum + parent.x, 0) / parents.length;
+    };
+    layer.forEach((node) => { node.center = center(node); });
+    layer.sort((a, b) => a.center - b.center || a.order - b.order);
+  }
+  let x = GAP;
+  const height = Math.max(...layer.map((node) => node.height));
+  layer.forEach((node) => {
+    node.x = x + node.width / 2;
+    node.y = y;
+    x += node.width + GAP;
+  });
+  layer.width = x;
+  width = Math.max(width, x);
+  y += height + LAYER;
+});
+// every layer is centered
+layers.forEach((layer) => layer.forEach((node) => { node.x += (width - layer.width) / 2; }));
+svg.setAttribute('viewBox', '0 0 ' + width + ' ' + (y - LAYER + GAP));
+svg.setAttribute('width', width);
+
+function element(name, attributes, parent) {
+  const e = document.createElementNS(NS, name);
+  Object.entries(attributes).forEach(([key, value]) => e.setAttribute(key, value));
+  parent.appendChild(e);
+  return e;
+}
+
+const defs = element('defs', {}, svg);
+const marker = element('marker', { id: 'arrow', viewBox: '0 0 10 10', refX: 10, refY: 5, markerWidth: 8, markerHeight: 8, orient: 'auto' }, defs);
+element('path', { d: 'M 0 0 L 10 5 L 0 10 z', style: 'fill: #999' }, marker);
+
+edges.forEach((edge) => {
+  const from = nodes.get(edge.from), to = nodes.get(edge.to);
+  const x1 = from.x, y1 = from.y + from.height, x2 = to.x, y2 = to.y;
+  const middle = (y1 + y2) / 2;
+  edge.elements = [element('path', {
+    d: `M ${x1} ${y1} C ${x1} ${middle}, ${x2} ${middle}, ${x2} ${y2}`,
+    'marker-end': 'url(#arrow)',
+  }, svg)];
+  if (edge.label) {
+    const label = element('text', { x: (x1 + x2) / 2, y: middle, class: 'edge' }, svg);
+    label.textContent = edge.label;
+    edge.elements.push(label);
+  }
+});
+
+nodes.forEach((node) => {
+  node.element = element('g', {}, svg);
+  element('title', {}, node.element).textContent = node.lines.join(' ');
+  element('rect', {
+    x: node.x - node.width / 2, y: node.y, width: node.width, height: node.height, rx: 4,
+    style: node.color ? 'fill: ' + node.color : '',
+  }, node.element);
+  node.lines.forEach((line, index) => {
+    const text = element('text', { x: node.x, y: node.y + 16 + index * 15 }, node.element);
+    // the last line is the availability
+    if (index === node.lines.length - 1 && index > 0) text.setAttribute('class', 'availability');
+    text.textContent = line;
+  });
+  node.element.onclick = (event) => {
+    event.stopPropagation();
+    highlight(node.id);
+  };
+});
+
+// highlight dims everything the node doesn't depend on, or nothing
+function highlight(id) {
+  const reached = new Set(id === undefined ? nodes.keys() : [id]);
+  for (let changed = id !== undefined; changed;) {
+    changed = false;
+    edges.forEach((edge) => {
+      if (reached.has(edge.from) && !reached.has(edge.to)) {
+        reached.add(edge.to);
+        changed = true;
+      }
+    });
+  }
+  nodes.forEach((node) => node.element.classList.toggle('dim', !reached.has(node.id)));
+  edges.forEach((edge) => edge.elements.forEach((e) => e.classList.toggle('dim', !reached.has(edge.from))));
+}
+svg.onclick = () => highlight();
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        calculate::{AtomRegistry, DumpTerm},
+        sla::{Dependency, Group, Service, ServiceGraph},
+    };
+
+    use super::{Report, ReportOptions};
+
+    #[test]
+    fn test_report() {
+        let database = Service::known_sla("database", 0.999);
+        let members = (0..3)
+            .map(|index| Service::known_sla(format!("replica_{}", index), 0.99))
+            .collect();
+        let group = Group::new(members, 2);
+        let service = Service::dependencies(vec![
+            Dependency::Service(database),
+            Dependency::Group(group),
+        ]);
+
+        let source = "services:\n  - name: </script><b>";
+        let mut report = Report::new("proposal", source, ReportOptions::default());
+        report.add("api", &service.dump_term(&mut AtomRegistry::default()));
+        let mut graph = ServiceGraph::default();
+        graph.name_service(&service, "api </script>");
+        graph.add(&service);
+        report.with_graph(&graph).unwrap();
+
+        let mut output = Vec::new();
+        report.render(&mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("<title>SLA report: proposal</title>"));
+        assert!(output.contains("<tr><td>api</td><td>99.87%</td>"));
+        // the single points of failure come first
+        assert!(output.contains("<tr><td>1.0000e-3</td><td>database</td></tr>"));
+        assert!(output.contains("<td>replica_0</td>"));
+        // the input is escaped, and doesn't end the embedded json early
+        assert!(output.contains("&lt;/script&gt;&lt;b&gt;"));
+        assert_eq!(output.matches("</script>").count(), 2);
+        assert!(output.contains("<svg id=\"diagram\""));
+        assert!(output.contains("<p>With at most 4 components.</p>"));
+    }
+}
@@ -14,4 +14,7 @@
 //
 
 pub mod calculate;
+pub mod config;
+pub mod error;
+pub mod export;
 pub mod sla;
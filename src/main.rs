@@ -1,3 +1,793 @@
-fn main() {
-    // TODO: read configuration from the
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{path::PathBuf, process::ExitCode, sync::Arc, time::Duration};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use slac::{
+    calculate::{
+        format_duration, AtomRegistry, Availability, DowntimeOptions, DumpTerm, Engine, Importance,
+        RenderOptions, Term, TermGraph, MONTH, QUARTER,
+    },
+    config::{parse_duration, parse_sla, Config, ConfigError, Topology},
+    export::{render_json, render_mermaid, Export, Report, ReportOptions},
+    sla::Service,
+};
+
+#[derive(Parser)]
+#[command(about = "A simple propositional logic probability calculator for SLA")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Calculate the theoretical SLA of the services in a config file
+    Calc {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Calculate this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The algorithm to calculate the probability
+        #[arg(short, long, value_enum, default_value_t = EngineArg::Bdd)]
+        engine: EngineArg,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// Also calculate the exact probability with rational numbers, and
+        /// print it with this number of digits. The slas in the config are
+        /// floating numbers, and calculated as their exact binary values, so
+        /// the digits beyond about 16 significant ones reflect the rounding of
+        /// the input rather than the calculation
+        #[cfg(feature = "exact")]
+        #[arg(long, value_name = "DIGITS")]
+        exact: Option<usize>,
+    },
+    /// Estimate the SLA of the services by sampling, for topologies which are
+    /// too large to calculate exactly
+    Estimate {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Estimate this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The number of samples
+        #[arg(short = 'n', long, default_value_t = 1_000_000, value_parser = parse_count_arg)]
+        samples: usize,
+
+        /// The seed of the random number generator, to reproduce a result
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+    },
+    /// Simulate the services over many billing periods, and report the
+    /// distribution of the downtime in a period
+    Downtime {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Simulate this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The number of simulated periods
+        #[arg(short = 'n', long, default_value_t = 10_000, value_parser = parse_count_arg)]
+        trials: usize,
+
+        /// The billing period, like "30d"
+        #[arg(short, long, value_parser = parse_positive_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// The mean duration of outages of the components without mtbf and
+        /// mttr
+        #[arg(long, value_parser = parse_positive_duration_arg, default_value = "1h")]
+        mean_outage: Duration,
+
+        /// Count the downtime in slots of this length, e.g. "1m" for a
+        /// per-minute SLA
+        #[arg(long, value_parser = parse_positive_duration_arg)]
+        granularity: Option<Duration>,
+
+        /// Also report the probability that the availability of a period is
+        /// lower than this sla, like "99.9%"
+        #[arg(long, value_parser = parse_sla_arg)]
+        target: Option<f64>,
+
+        /// The seed of the random number generator, to reproduce a result
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Calculate the credits paid for the services in every billing period,
+    /// by the providers and to the customers
+    Credit {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Analyze this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The number of simulated periods
+        #[arg(short = 'n', long, default_value_t = 10_000, value_parser = parse_count_arg)]
+        trials: usize,
+
+        /// The billing period, like "30d"
+        #[arg(short, long, value_parser = parse_positive_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// The mean duration of outages of the components without mtbf and
+        /// mttr
+        #[arg(long, value_parser = parse_positive_duration_arg, default_value = "1h")]
+        mean_outage: Duration,
+
+        /// Count the downtime in slots of this length, e.g. "1m" for a
+        /// per-minute SLA
+        #[arg(long, value_parser = parse_positive_duration_arg)]
+        granularity: Option<Duration>,
+
+        /// The seed of the random number generator, to reproduce a result
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Compare the services with the target SLO, and report the error budget
+    /// consumed by the design. Exits with the code 3 if any service is below
+    /// the target, and 1 if the input is invalid.
+    Budget {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Analyze this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The target SLO, like "99.9%"
+        #[arg(short, long, value_parser = parse_target_arg)]
+        target: f64,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// Print at most this number of the dependencies which consume the
+        /// most budget
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+    },
+    /// List the minimal combinations of component failures which take the
+    /// services down, ranked by the probability
+    CutSets {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Analyze this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// Skip the cut sets with more components than this
+        #[arg(long)]
+        max_order: Option<usize>,
+
+        /// Print at most this number of cut sets for every service
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Rank the components by their importance measures, to find out which
+    /// dependency should be hardened first
+    Importance {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Analyze this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// The importance measure to sort the components by
+        #[arg(long, value_enum, default_value_t = Measure::Birnbaum)]
+        sort_by: Measure,
+    },
+    /// Write a self-contained html report of the services, with the
+    /// topology, the availability, the minimal cut sets, the importance of
+    /// every component and an interactive dependency diagram
+    Report {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Report this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// Write the report to this file instead of the stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// Skip the cut sets with more components than this
+        #[arg(long, default_value_t = 4)]
+        max_order: usize,
+
+        /// List at most this number of cut sets for every service
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Draw the logic tree of the services
+    Tree {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Draw this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// Draw every component once, rather than at every position in the
+        /// tree
+        #[arg(long)]
+        merge_atoms: bool,
+
+        /// Label every node with its probability
+        #[arg(long)]
+        annotate: bool,
+
+        /// Fill every node by its contribution to the unavailability
+        #[arg(long)]
+        heat_map: bool,
+
+        /// The output format
+        #[arg(short, long, value_enum, default_value_t = Format::Dot)]
+        format: Format,
+    },
+    /// Draw the dependency graph of the services, with the availability of
+    /// every service and group
+    Graph {
+        /// Path to the config file, in yaml, toml or json format
+        config: PathBuf,
+
+        /// Draw this service instead of the targets in the config
+        #[arg(short, long)]
+        service: Option<String>,
+
+        /// The period to derive the sla of services with a failure rate, like
+        /// "30d" or "1y"
+        #[arg(short, long, value_parser = parse_duration_arg, default_value = "30d")]
+        window: Duration,
+
+        /// The output format
+        #[arg(short, long, value_enum, default_value_t = Format::Dot)]
+        format: Format,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Measure {
+    Birnbaum,
+    FussellVesely,
+    Raw,
+    Rrw,
+}
+
+impl Measure {
+    fn of(&self, importance: &Importance) -> f64 {
+        match self {
+            Measure::Birnbaum => importance.birnbaum(),
+            Measure::FussellVesely => importance.fussell_vesely(),
+            Measure::Raw => importance.risk_achievement_worth(),
+            Measure::Rrw => importance.risk_reduction_worth(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+// render writes the graph to the stdout in the format
+fn render<'a, N, E, G>(graph: &'a G, format: Format) -> Result<(), ConfigError>
+where
+    N: Clone + 'a,
+    E: Clone + 'a,
+    G: Export<'a, N, E>,
+{
+    let mut stdout = std::io::stdout();
+    match format {
+        Format::Dot => dot::render(graph, &mut stdout),
+        Format::Mermaid => render_mermaid(graph, &mut stdout),
+        Format::Json => render_json(graph, &mut stdout),
+    }
+    .map_err(ConfigError::Io)
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum EngineArg {
+    InclusionExclusion,
+    Bdd,
+}
+
+impl From<EngineArg> for Engine {
+    fn from(engine: EngineArg) -> Engine {
+        match engine {
+            EngineArg::InclusionExclusion => Engine::InclusionExclusion,
+            EngineArg::Bdd => Engine::Bdd,
+        }
+    }
+}
+
+fn parse_duration_arg(text: &str) -> Result<Duration, String> {
+    parse_duration(text).ok_or_else(|| format!("invalid duration {:?}, expected like 30d", text))
+}
+
+fn parse_positive_duration_arg(text: &str) -> Result<Duration, String> {
+    match parse_duration(text) {
+        Some(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err(format!(
+            "invalid duration {:?}, expected a positive one like 1h",
+            text
+        )),
+    }
+}
+
+fn parse_count_arg(text: &str) -> Result<usize, String> {
+    match text.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(count),
+        _ => Err(format!(
+            "invalid count {:?}, expected a positive integer",
+            text
+        )),
+    }
+}
+
+fn parse_sla_arg(text: &str) -> Result<f64, String> {
+    match parse_sla(text) {
+        Some(sla) if (0.0..=1.0).contains(&sla) => Ok(sla),
+        _ => Err(format!("invalid sla {:?}, expected like 99.9%", text)),
+    }
+}
+
+// parse_target_arg parses the target of an error budget, which should be
+// lower than 100% to leave any budget
+fn parse_target_arg(text: &str) -> Result<f64, String> {
+    match parse_sla(text) {
+        Some(target) if (0.0..1.0).contains(&target) => Ok(target),
+        _ => Err(format!(
+            "invalid target {:?}, expected lower than 100% like 99.9%",
+            text
+        )),
+    }
+}
+
+// select returns the services to calculate: the one specified in the command
+// line, or the targets of the topology. Only the targets are validated when
+// the topology is built, so the specified one is validated here.
+fn select<'a>(
+    topology: &'a Topology,
+    service: &'a Option<String>,
+) -> Result<Vec<(&'a str, &'a Arc<Service>)>, ConfigError> {
+    match service {
+        Some(name) => match topology.service(name) {
+            Some(svc) => {
+                svc.validate().map_err(|source| ConfigError::Invalid {
+                    name: name.clone(),
+                    source,
+                })?;
+                Ok(vec![(name.as_str(), svc)])
+            }
+            None => Err(ConfigError::UnknownTarget(name.clone())),
+        },
+        None => Ok(topology.targets().collect()),
+    }
+}
+
+// dump loads the config and returns the terms of the services to calculate
+fn dump(
+    path: &PathBuf,
+    service: &Option<String>,
+    mission_time: Duration,
+) -> Result<Vec<(String, Term)>, ConfigError> {
+    let topology = Config::load(path)?.build()?;
+
+    Ok(select(&topology, service)?
+        .into_iter()
+        .map(|(name, svc)| {
+            let mut registry = AtomRegistry::with_mission_time(mission_time);
+            (name.to_string(), svc.dump_term(&mut registry))
+        })
+        .collect())
+}
+
+// BELOW_TARGET is the exit code of a design which misses the target, which is
+// distinct from 1 for an invalid input and 2 for an invalid command line
+const BELOW_TARGET: u8 = 3;
+
+// run executes the command, and returns the exit code if it succeeds
+fn run(cli: Cli) -> Result<ExitCode, ConfigError> {
+    match cli.command {
+        Command::Calc {
+            config,
+            service,
+            engine,
+            window,
+            #[cfg(feature = "exact")]
+            exact,
+        } => {
+            for (name, term) in dump(&config, &service, window)? {
+                let availability = match engine {
+                    EngineArg::InclusionExclusion => {
+                        Availability::from_probability(term.calc_with(Engine::InclusionExclusion))
+                    }
+                    EngineArg::Bdd => term.availability(),
+                };
+                println!(
+                    "{}: {} ({:.2} nines, {} downtime per month, {} per year)",
+                    name,
+                    availability.availability(),
+                    availability.nines(),
+                    format_duration(availability.downtime_per_month()),
+                    format_duration(availability.downtime_per_year())
+                );
+
+                #[cfg(feature = "exact")]
+                if let Some(digits) = exact {
+                    let probability = term.calc_exact();
+                    println!(
+                        "{} (exact): {}",
+                        name,
+                        slac::calculate::to_decimal(&probability, digits)
+                    );
+                }
+            }
+        }
+        Command::Estimate {
+            config,
+            service,
+            samples,
+            seed,
+            window,
+        } => {
+            for (name, term) in dump(&config, &service, window)? {
+                let estimate = match seed {
+                    Some(seed) => term.estimate_with_seed(samples, seed),
+                    None => term.estimate(samples, &mut rand::thread_rng()),
+                };
+                let (lower, upper) = estimate.confidence_interval();
+                println!(
+                    "{}: {} (95% confidence interval [{}, {}], {} samples)",
+                    name,
+                    estimate.probability(),
+                    lower,
+                    upper,
+                    estimate.samples()
+                );
+            }
+        }
+        Command::Downtime {
+            config,
+            service,
+            trials,
+            window,
+            mean_outage,
+            granularity,
+            target,
+            seed,
+        } => {
+            let options = DowntimeOptions {
+                period: window,
+                mean_outage,
+                granularity,
+            };
+            for (name, term) in dump(&config, &service, window)? {
+                let downtime = match seed {
+                    Some(seed) => term.simulate_downtime_with_seed(trials, &options, seed),
+                    None => term.simulate_downtime(trials, &options, &mut rand::thread_rng()),
+                };
+
+                println!("{}: downtime in {}", name, format_duration(window));
+                println!("  mean   {}", format_duration(downtime.mean()));
+                for percentile in [50.0, 90.0, 99.0, 99.9] {
+                    println!(
+                        "  p{:<5} {}",
+                        percentile,
+                        format_duration(downtime.percentile(percentile))
+                    );
+                }
+                println!("  max    {}", format_duration(downtime.percentile(100.0)));
+                if let Some(target) = target {
+                    println!(
+                        "  {:.4}% of the periods are below {}%",
+                        downtime.breach_probability(target) * 100.0,
+                        target * 100.0
+                    );
+                }
+            }
+        }
+        Command::Credit {
+            config,
+            service,
+            trials,
+            window,
+            mean_outage,
+            granularity,
+            seed,
+        } => {
+            let topology = Config::load(&config)?.build()?;
+            let options = DowntimeOptions {
+                period: window,
+                mean_outage,
+                granularity,
+            };
+            let mut rng: Box<dyn RngCore> = match seed {
+                Some(seed) => Box::new(StdRng::seed_from_u64(seed)),
+                None => Box::new(rand::thread_rng()),
+            };
+
+            for (name, svc) in select(&topology, &service)? {
+                let mut registry = AtomRegistry::with_mission_time(window);
+                let term = svc.dump_term(&mut registry);
+                println!("{}:", name);
+
+                match topology.credits() {
+                    Some(policy) => {
+                        let downtime = term.simulate_downtime(trials, &options, &mut rng);
+                        println!(
+                            "  owed to customers: {:.4}% of the bill",
+                            downtime.expected_credit(policy) * 100.0
+                        );
+                        let probabilities = downtime.tier_probabilities(policy);
+                        for (tier, probability) in policy.tiers().iter().zip(probabilities) {
+                            println!(
+                                "    {}% credit below {}%: {:.4}% of the periods",
+                                tier.credit() * 100.0,
+                                tier.below() * 100.0,
+                                probability * 100.0
+                            );
+                        }
+                    }
+                    None => println!("  owed to customers: no credits in the config"),
+                }
+
+                println!("  owed by providers:");
+                for (provider, sla, policy) in svc.credit_policies() {
+                    // the credit only depends on the availability of the
+                    // provider itself, which is not split even if it's a
+                    // member of a common cause group
+                    let atom = AtomRegistry::default().new_atom(provider.to_string(), sla);
+                    let downtime = Term::atom(atom).simulate_downtime(trials, &options, &mut rng);
+                    println!(
+                        "    {}: {:.4}% of the bill",
+                        provider,
+                        downtime.expected_credit(policy) * 100.0
+                    );
+                }
+            }
+        }
+        Command::Budget {
+            config,
+            service,
+            target,
+            window,
+            top,
+        } => {
+            let mut code = ExitCode::SUCCESS;
+            for (name, term) in dump(&config, &service, window)? {
+                let budget = term.error_budget(target);
+                println!(
+                    "{}: {} against the target {}% ({:.2}% of the budget consumed)",
+                    name,
+                    1.0 - budget.unavailability(),
+                    target * 100.0,
+                    budget.consumption() * 100.0
+                );
+                for (period, length) in [("month", MONTH), ("quarter", QUARTER)] {
+                    println!(
+                        "  budget per {}: {}, consumed {}",
+                        period,
+                        format_duration(budget.budget(length)),
+                        format_duration(budget.consumed(length))
+                    );
+                }
+
+                println!("  top consumers per month:");
+                for consumer in budget.consumers().iter().take(top) {
+                    println!(
+                        "    {}: {} ({:.2}% of the budget)",
+                        consumer.atom().name(),
+                        format_duration(MONTH.mul_f64(consumer.contribution())),
+                        consumer.contribution() / (1.0 - target) * 100.0
+                    );
+                }
+
+                if !budget.is_met() {
+                    eprintln!("{} is below the target {}%", name, target * 100.0);
+                    code = ExitCode::from(BELOW_TARGET);
+                }
+            }
+            return Ok(code);
+        }
+        Command::CutSets {
+            config,
+            service,
+            window,
+            max_order,
+            limit,
+        } => {
+            for (name, term) in dump(&config, &service, window)? {
+                println!("{}:", name);
+
+                let cut_sets = term.minimal_cut_sets(max_order);
+                for cut_set in cut_sets.iter().take(limit.unwrap_or(usize::MAX)) {
+                    let atoms: Vec<&str> = cut_set.atoms().iter().map(|atom| atom.name()).collect();
+                    println!("  {:.4e}\t{}", cut_set.probability(), atoms.join(", "));
+                }
+            }
+        }
+        Command::Importance {
+            config,
+            service,
+            window,
+            sort_by,
+        } => {
+            for (name, term) in dump(&config, &service, window)? {
+                println!("{}:", name);
+
+                let mut importance = term.importance();
+                importance.sort_by(|a, b| sort_by.of(b).total_cmp(&sort_by.of(a)));
+
+                let width = importance
+                    .iter()
+                    .map(|item| item.atom().name().len())
+                    .max()
+                    .unwrap_or(0)
+                    .max("component".len());
+                println!(
+                    "  {:<width$}  {:>12}  {:>14}  {:>12}  {:>12}",
+                    "component",
+                    "birnbaum",
+                    "fussell-vesely",
+                    "raw",
+                    "rrw",
+                    width = width
+                );
+                for item in importance.iter() {
+                    println!(
+                        "  {:<width$}  {:>12.4e}  {:>14.4e}  {:>12.4}  {:>12.4}",
+                        item.atom().name(),
+                        item.birnbaum(),
+                        item.fussell_vesely(),
+                        item.risk_achievement_worth(),
+                        item.risk_reduction_worth(),
+                        width = width
+                    );
+                }
+            }
+        }
+        Command::Report {
+            config,
+            service,
+            output,
+            window,
+            max_order,
+            limit,
+        } => {
+            let source = std::fs::read_to_string(&config).map_err(ConfigError::Io)?;
+            let topology = Config::load(&config)?.build()?;
+            let services = select(&topology, &service)?;
+
+            let title = config
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let mut report = Report::new(
+                title,
+                source,
+                ReportOptions {
+                    max_order: Some(max_order),
+                    limit,
+                },
+            );
+            for (name, svc) in services.iter() {
+                let mut registry = AtomRegistry::with_mission_time(window);
+                report.add(name, &svc.dump_term(&mut registry));
+            }
+            let graph = topology.graph(services.iter().map(|(_, svc)| *svc), window);
+            report.with_graph(&graph).map_err(ConfigError::Io)?;
+
+            match output {
+                Some(path) => {
+                    let mut file = std::fs::File::create(path).map_err(ConfigError::Io)?;
+                    report.render(&mut file)
+                }
+                None => report.render(&mut std::io::stdout()),
+            }
+            .map_err(ConfigError::Io)?;
+        }
+        Command::Tree {
+            config,
+            service,
+            window,
+            merge_atoms,
+            annotate,
+            heat_map,
+            format,
+        } => {
+            for (name, term) in dump(&config, &service, window)? {
+                let options = RenderOptions {
+                    graph_id: name,
+                    merge_atoms,
+                    annotate,
+                    heat_map,
+                };
+                render(&TermGraph::with_options(&term, options), format)?;
+            }
+        }
+        Command::Graph {
+            config,
+            service,
+            window,
+            format,
+        } => {
+            let topology = Config::load(&config)?.build()?;
+            let services = select(&topology, &service)?;
+            let graph = topology.graph(services.into_iter().map(|(_, svc)| svc), window);
+            render(&graph, format)?;
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
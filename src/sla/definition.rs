@@ -13,69 +13,597 @@
 // limitations under the License.
 //
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
-use crate::calculate::{AtomRegistry, DumpTerm, Term};
-
-use itertools::Itertools;
+use crate::{
+    calculate::{Atom, AtomRegistry, CreditPolicy, DumpTerm, OutageModel, Term},
+    error::{check_sla, SlacError},
+};
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Service {
-    KnownSLA { name: String, sla: f64 },
+    // the service is available iff its own atom and all of its fault domains
+    // are available
+    KnownSLA {
+        name: String,
+        sla: f64,
+        #[cfg_attr(feature = "serde", serde(default))]
+        domains: Vec<Arc<FaultDomain>>,
+        // the credit tiers paid by the provider when the sla is breached
+        #[cfg_attr(feature = "serde", serde(default))]
+        credits: Option<CreditPolicy>,
+    },
+    // the sla is derived from the failure model of the service
+    Modeled {
+        name: String,
+        model: FailureModel,
+        #[cfg_attr(feature = "serde", serde(default))]
+        domains: Vec<Arc<FaultDomain>>,
+    },
     Dependencies(Vec<Dependency>),
+    Program(Program),
+}
+
+// FailureModel describes a component by its failure and repair times, as
+// given by most vendors, rather than a predefined SLA
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FailureModel {
+    // a repairable component, whose steady-state availability is
+    // mtbf / (mtbf + mttr) in any period
+    Repairable { mtbf: Duration, mttr: Duration },
+    // a component which fails with a constant rate (failures per hour) and
+    // is not repaired in the mission time t, so its reliability is e^(-rate * t)
+    FailureRate { rate: f64 },
+}
+
+// Infra is a machine with predefined SLA
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Infra {
+    name: String,
+    sla: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    domains: Vec<Arc<FaultDomain>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum DomainKind {
+    Region,
+    Zone,
+    Rack,
+}
+
+// FaultDomain is a region, zone or rack shared by many components, with its
+// own outage probability. An outage of the domain takes down all components
+// in it, so the failures of these components are correlated. A domain is
+// available iff itself and its parent are available.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaultDomain {
+    name: String,
+    kind: DomainKind,
+    sla: f64,
+    parent: Option<Arc<FaultDomain>>,
+}
+
+// Connection is a network connection with predefined SLA. It could represent a
+// connection between two machines, or a total connection plane.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Connection {
+    name: String,
+    sla: f64,
+}
+
+// Program is a running software on an `Infra`, which reaches each of its
+// dependencies through a `Connection`.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Program {
+    name: String,
+    infra: Arc<Infra>,
+    dependencies: Vec<(Arc<Connection>, Dependency)>,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Group {
     dependencies: Vec<Arc<Service>>,
     quorum: usize,
+
+    // the beta factor of the common cause failure, which is the fraction of
+    // each member's unavailability caused by a failure shared by all members
+    #[cfg_attr(feature = "serde", serde(default))]
+    beta: Option<f64>,
 }
 
 // Actually, a service and a group doesn't have too much difference just left
 // here for the convinience.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Dependency {
-    // The user actually can cause loop reference, in which case Arc will fail
-    // to work. However, this program also cannot work well under the loop
-    // dependency, so the user should handle the loop reference situation by
-    // themselves.
+    // A dependency must be constructed before its dependents, so the `Arc`
+    // wiring cannot form a loop. The named references in a config file could
+    // form a loop, which is detected and reported by `Config::build` before
+    // constructing the model.
     Service(Arc<Service>),
     Group(Arc<Group>),
 }
 
 pub struct AtomAllocator {}
 
+// The infallible constructors panic on invalid inputs, use the `try_` ones to
+// handle the errors.
 impl Service {
     pub fn known_sla<S: AsRef<str>>(name: S, sla: f64) -> Arc<Service> {
-        Arc::new(Service::KnownSLA {
+        Service::try_known_sla(name, sla).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_known_sla<S: AsRef<str>>(name: S, sla: f64) -> Result<Arc<Service>, SlacError> {
+        Service::try_in_domains(name, sla, Vec::new())
+    }
+
+    // in_domains constructs a service with predefined SLA, which runs in the
+    // fault domains
+    pub fn in_domains<S: AsRef<str>>(
+        name: S,
+        sla: f64,
+        domains: Vec<Arc<FaultDomain>>,
+    ) -> Arc<Service> {
+        Service::try_in_domains(name, sla, domains).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_in_domains<S: AsRef<str>>(
+        name: S,
+        sla: f64,
+        domains: Vec<Arc<FaultDomain>>,
+    ) -> Result<Arc<Service>, SlacError> {
+        check_sla(name.as_ref(), sla)?;
+
+        Ok(Arc::new(Service::KnownSLA {
+            name: name.as_ref().to_string(),
+            sla,
+            domains,
+            credits: None,
+        }))
+    }
+
+    // with_credits constructs a service provided by others, who pays credits
+    // when the sla is breached
+    pub fn with_credits<S: AsRef<str>>(
+        name: S,
+        sla: f64,
+        domains: Vec<Arc<FaultDomain>>,
+        credits: CreditPolicy,
+    ) -> Arc<Service> {
+        Service::try_with_credits(name, sla, domains, credits)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_with_credits<S: AsRef<str>>(
+        name: S,
+        sla: f64,
+        domains: Vec<Arc<FaultDomain>>,
+        credits: CreditPolicy,
+    ) -> Result<Arc<Service>, SlacError> {
+        check_sla(name.as_ref(), sla)?;
+
+        Ok(Arc::new(Service::KnownSLA {
             name: name.as_ref().to_string(),
             sla,
-        })
+            domains,
+            credits: Some(credits),
+        }))
+    }
+
+    // credit_policies returns the services which this service depends on and
+    // pay credits, with their slas and credit tiers
+    pub fn credit_policies(&self) -> Vec<(&str, f64, &CreditPolicy)> {
+        fn collect<'a>(
+            service: &'a Service,
+            seen: &mut HashSet<&'a str>,
+            policies: &mut Vec<(&'a str, f64, &'a CreditPolicy)>,
+        ) {
+            let dependencies: Vec<&'a Dependency> = match service {
+                Service::KnownSLA {
+                    name,
+                    sla,
+                    credits: Some(credits),
+                    ..
+                } => {
+                    if seen.insert(name) {
+                        policies.push((name, *sla, credits));
+                    }
+                    return;
+                }
+                Service::KnownSLA { .. } | Service::Modeled { .. } => return,
+                Service::Dependencies(dependencies) => dependencies.iter().collect(),
+                Service::Program(program) => {
+                    program.dependencies.iter().map(|(_, dep)| dep).collect()
+                }
+            };
+
+            for dep in dependencies {
+                match dep {
+                    Dependency::Service(svc) => collect(svc, seen, policies),
+                    Dependency::Group(group) => {
+                        for svc in group.dependencies.iter() {
+                            collect(svc, seen, policies);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut policies = Vec::new();
+        collect(self, &mut HashSet::new(), &mut policies);
+        policies
+    }
+
+    pub fn modeled<S: AsRef<str>>(
+        name: S,
+        model: FailureModel,
+        domains: Vec<Arc<FaultDomain>>,
+    ) -> Arc<Service> {
+        Service::try_modeled(name, model, domains).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_modeled<S: AsRef<str>>(
+        name: S,
+        model: FailureModel,
+        domains: Vec<Arc<FaultDomain>>,
+    ) -> Result<Arc<Service>, SlacError> {
+        model.check(name.as_ref())?;
+
+        Ok(Arc::new(Service::Modeled {
+            name: name.as_ref().to_string(),
+            model,
+            domains,
+        }))
     }
 
     pub fn dependencies(dependencies: Vec<Dependency>) -> Arc<Service> {
         Arc::new(Service::Dependencies(dependencies))
     }
+
+    pub fn program<S: AsRef<str>>(
+        name: S,
+        infra: Arc<Infra>,
+        dependencies: Vec<(Arc<Connection>, Dependency)>,
+    ) -> Arc<Service> {
+        Arc::new(Service::Program(Program {
+            name: name.as_ref().to_string(),
+            infra,
+            dependencies,
+        }))
+    }
+}
+
+impl Infra {
+    pub fn new<S: AsRef<str>>(name: S, sla: f64) -> Arc<Infra> {
+        Infra::try_new(name, sla).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new<S: AsRef<str>>(name: S, sla: f64) -> Result<Arc<Infra>, SlacError> {
+        Infra::try_in_domains(name, sla, Vec::new())
+    }
+
+    pub fn in_domains<S: AsRef<str>>(
+        name: S,
+        sla: f64,
+        domains: Vec<Arc<FaultDomain>>,
+    ) -> Arc<Infra> {
+        Infra::try_in_domains(name, sla, domains).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_in_domains<S: AsRef<str>>(
+        name: S,
+        sla: f64,
+        domains: Vec<Arc<FaultDomain>>,
+    ) -> Result<Arc<Infra>, SlacError> {
+        check_sla(name.as_ref(), sla)?;
+
+        Ok(Arc::new(Infra {
+            name: name.as_ref().to_string(),
+            sla,
+            domains,
+        }))
+    }
+
+    /// Get a reference to the infra's name.
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Get the infra's sla.
+    pub fn sla(&self) -> f64 {
+        self.sla
+    }
+
+    /// Get a reference to the fault domains of the infra.
+    pub fn domains(&self) -> &[Arc<FaultDomain>] {
+        self.domains.as_ref()
+    }
+}
+
+impl FailureModel {
+    pub(crate) fn check(&self, name: &str) -> Result<(), SlacError> {
+        let valid = match self {
+            FailureModel::Repairable { mtbf, .. } => !mtbf.is_zero(),
+            FailureModel::FailureRate { rate } => *rate >= 0.0 && rate.is_finite(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(SlacError::InvalidFailureModel {
+                name: name.to_string(),
+            })
+        }
+    }
+
+    // probability returns the probability that the component is available in
+    // the mission time
+    pub fn probability(&self, mission_time: Duration) -> f64 {
+        match self {
+            FailureModel::Repairable { mtbf, mttr } => {
+                mtbf.as_secs_f64() / (mtbf.as_secs_f64() + mttr.as_secs_f64())
+            }
+            FailureModel::FailureRate { rate } => {
+                (-rate * mission_time.as_secs_f64() / 3600.0).exp()
+            }
+        }
+    }
+}
+
+impl FaultDomain {
+    pub fn new<S: AsRef<str>>(
+        name: S,
+        kind: DomainKind,
+        sla: f64,
+        parent: Option<Arc<FaultDomain>>,
+    ) -> Arc<FaultDomain> {
+        FaultDomain::try_new(name, kind, sla, parent).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new<S: AsRef<str>>(
+        name: S,
+        kind: DomainKind,
+        sla: f64,
+        parent: Option<Arc<FaultDomain>>,
+    ) -> Result<Arc<FaultDomain>, SlacError> {
+        check_sla(name.as_ref(), sla)?;
+
+        Ok(Arc::new(FaultDomain {
+            name: name.as_ref().to_string(),
+            kind,
+            sla,
+            parent,
+        }))
+    }
+
+    /// Get a reference to the domain's name.
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Get the domain's kind.
+    pub fn kind(&self) -> DomainKind {
+        self.kind
+    }
+
+    /// Get the domain's sla.
+    pub fn sla(&self) -> f64 {
+        self.sla
+    }
+
+    /// Get a reference to the parent domain, e.g. the region of a zone.
+    pub fn parent(&self) -> Option<&Arc<FaultDomain>> {
+        self.parent.as_ref()
+    }
+}
+
+impl Connection {
+    pub fn new<S: AsRef<str>>(name: S, sla: f64) -> Arc<Connection> {
+        Connection::try_new(name, sla).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new<S: AsRef<str>>(name: S, sla: f64) -> Result<Arc<Connection>, SlacError> {
+        check_sla(name.as_ref(), sla)?;
+
+        Ok(Arc::new(Connection {
+            name: name.as_ref().to_string(),
+            sla,
+        }))
+    }
+
+    /// Get a reference to the connection's name.
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Get the connection's sla.
+    pub fn sla(&self) -> f64 {
+        self.sla
+    }
+}
+
+impl Program {
+    /// Get a reference to the program's name.
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Get a reference to the infra this program runs on.
+    pub fn infra(&self) -> &Arc<Infra> {
+        &self.infra
+    }
+
+    /// Get a reference to the program's dependencies and the connections to
+    /// reach them.
+    pub fn dependencies(&self) -> &[(Arc<Connection>, Dependency)] {
+        self.dependencies.as_ref()
+    }
 }
 
 impl Group {
     pub fn new(dependencies: Vec<Arc<Service>>, quorum: usize) -> Arc<Group> {
-        Arc::new(Group {
+        Group::try_new(dependencies, quorum).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_new(
+        dependencies: Vec<Arc<Service>>,
+        quorum: usize,
+    ) -> Result<Arc<Group>, SlacError> {
+        check_quorum(quorum, dependencies.len())?;
+
+        Ok(Arc::new(Group {
+            dependencies,
+            quorum,
+            beta: None,
+        }))
+    }
+
+    // with_common_cause constructs a group of identical replicas, whose
+    // failures are split into independent failures and a common cause failure
+    // with the beta factor model
+    pub fn with_common_cause(
+        dependencies: Vec<Arc<Service>>,
+        quorum: usize,
+        beta: f64,
+    ) -> Arc<Group> {
+        Group::try_with_common_cause(dependencies, quorum, beta)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_with_common_cause(
+        dependencies: Vec<Arc<Service>>,
+        quorum: usize,
+        beta: f64,
+    ) -> Result<Arc<Group>, SlacError> {
+        check_beta(beta)?;
+        check_quorum(quorum, dependencies.len())?;
+
+        let group = Group {
             dependencies,
             quorum,
-        })
+            beta: Some(beta),
+        };
+        group.common_cause_sla()?;
+
+        Ok(Arc::new(group))
+    }
+
+    /// Get a reference to the members of the group.
+    pub fn dependencies(&self) -> &[Arc<Service>] {
+        self.dependencies.as_ref()
+    }
+
+    /// Get the minimum number of available members.
+    pub fn quorum(&self) -> usize {
+        self.quorum
+    }
+
+    /// Get the beta factor of the common cause failure.
+    pub fn beta(&self) -> Option<f64> {
+        self.beta
+    }
+
+    // common_cause_sla returns the sla shared by all members, and checks
+    // that every member is a service with predefined sla
+    pub(crate) fn common_cause_sla(&self) -> Result<f64, SlacError> {
+        let mut common = None;
+        for member in self.dependencies.iter() {
+            let (name, sla) = match member.as_ref() {
+                Service::KnownSLA { name, sla, .. } => (name.as_str(), Some(*sla)),
+                Service::Modeled { name, .. } => (name.as_str(), None),
+                Service::Dependencies(_) => ("a service with dependencies", None),
+                Service::Program(program) => (program.name(), None),
+            };
+            match (common, sla) {
+                (None, Some(sla)) => common = Some(sla),
+                (Some(common), Some(sla)) if common == sla => {}
+                _ => {
+                    return Err(SlacError::NonIdenticalMember {
+                        name: name.to_string(),
+                    })
+                }
+            }
+        }
+        Ok(common.unwrap_or(1.0))
+    }
+
+    // common_cause_atoms returns the probability of the independent atom of
+    // each member, and the common cause atom. With the unavailability Q of a
+    // member, the independent part fails with (1 - beta) * Q, and the common
+    // cause fails with beta * Q.
+    pub(crate) fn common_cause_atoms(&self, beta: f64) -> (f64, String, f64) {
+        let unavailability = 1.0 - self.common_cause_sla().expect("validated on construction");
+        let names: Vec<&str> = self
+            .dependencies
+            .iter()
+            .map(|member| match member.as_ref() {
+                Service::KnownSLA { name, .. } => name.as_str(),
+                _ => unreachable!("validated on construction"),
+            })
+            .collect();
+
+        (
+            1.0 - (1.0 - beta) * unavailability,
+            format!("ccf({})", names.join(", ")),
+            1.0 - beta * unavailability,
+        )
     }
 }
 
+// independent_atom returns the name of the independent part of a member of a
+// common cause group
+pub(crate) fn independent_atom(name: &str) -> String {
+    format!("{}/independent", name)
+}
+
+pub(crate) fn check_beta(beta: f64) -> Result<(), SlacError> {
+    if !(0.0..=1.0).contains(&beta) {
+        return Err(SlacError::InvalidBeta { beta });
+    }
+    Ok(())
+}
+
+pub(crate) fn check_quorum(quorum: usize, members: usize) -> Result<(), SlacError> {
+    if quorum == 0 || quorum > members {
+        return Err(SlacError::InvalidQuorum { quorum, members });
+    }
+    Ok(())
+}
+
 impl DumpTerm for Service {
     fn dump_term(&self, registry: &mut AtomRegistry) -> Term {
         match &self {
-            Service::KnownSLA { name, sla } => Term::atom(registry.new_atom(name.clone(), *sla)),
+            Service::KnownSLA {
+                name, sla, domains, ..
+            } => in_domains(registry.new_atom(name.clone(), *sla), domains, registry),
+            Service::Modeled {
+                name,
+                model,
+                domains,
+            } => {
+                // the outages of a repairable service are simulated with its
+                // mtbf and mttr
+                let atom = match model {
+                    FailureModel::Repairable { mtbf, mttr } => {
+                        registry.new_atom_with_outage(name.clone(), OutageModel::new(*mtbf, *mttr))
+                    }
+                    FailureModel::FailureRate { .. } => {
+                        let probability = model.probability(registry.mission_time());
+                        registry.new_atom(name.clone(), probability)
+                    }
+                };
+                in_domains(atom, domains, registry)
+            }
             Service::Dependencies(dependencies) => {
                 let mut intersects: Vec<Term> = Vec::new();
 
                 for dep in dependencies {
-                    match dep {
-                        Dependency::Service(svc) => intersects.push(svc.dump_term(registry)),
-                        Dependency::Group(group) => intersects.push(group.dump_term(registry)),
-                    }
+                    intersects.push(dep.dump_term(registry));
                 }
 
                 if intersects.is_empty() {
@@ -84,43 +612,133 @@ impl DumpTerm for Service {
                     Term::intersect(intersects)
                 }
             }
+            Service::Program(program) => program.dump_term(registry),
         }
     }
 }
 
-impl DumpTerm for Group {
+impl DumpTerm for Dependency {
+    fn dump_term(&self, registry: &mut AtomRegistry) -> Term {
+        match self {
+            Dependency::Service(svc) => svc.dump_term(registry),
+            Dependency::Group(group) => group.dump_term(registry),
+        }
+    }
+}
+
+impl DumpTerm for Infra {
     fn dump_term(&self, registry: &mut AtomRegistry) -> Term {
-        let mut unions: Vec<Term> = Vec::new();
+        in_domains(
+            registry.new_atom(self.name.clone(), self.sla),
+            &self.domains,
+            registry,
+        )
+    }
+}
 
-        let total = self.dependencies.len();
-        for success_count in self.quorum..total {
-            for svcs in self.dependencies.iter().combinations(success_count) {
-                let mut intersects: Vec<Term> = Vec::new();
-                for svc in svcs {
-                    intersects.push(svc.dump_term(registry));
-                }
+impl DumpTerm for FaultDomain {
+    fn dump_term(&self, registry: &mut AtomRegistry) -> Term {
+        let atom = registry.new_atom(self.name.clone(), self.sla);
+        match &self.parent {
+            Some(parent) => Term::intersect(vec![Term::atom(atom), parent.dump_term(registry)]),
+            None => Term::atom(atom),
+        }
+    }
+}
 
-                if !intersects.is_empty() {
-                    unions.push(Term::intersect(intersects));
-                }
-            }
+// in_domains represents a component as its own atom and all of its domains'
+// atoms, so the components sharing a domain share the same atoms
+fn in_domains(atom: Arc<Atom>, domains: &[Arc<FaultDomain>], registry: &mut AtomRegistry) -> Term {
+    if domains.is_empty() {
+        return Term::atom(atom);
+    }
+
+    let mut intersects = vec![Term::atom(atom)];
+    for domain in domains {
+        intersects.push(domain.dump_term(registry));
+    }
+    Term::intersect(intersects)
+}
+
+impl DumpTerm for Connection {
+    fn dump_term(&self, registry: &mut AtomRegistry) -> Term {
+        Term::atom(registry.new_atom(self.name.clone(), self.sla))
+    }
+}
+
+impl DumpTerm for Program {
+    fn dump_term(&self, registry: &mut AtomRegistry) -> Term {
+        // a program is available iff its infra is available, and every
+        // dependency is available and reachable through the connection
+        let mut intersects: Vec<Term> = vec![self.infra.dump_term(registry)];
+
+        for (connection, dep) in self.dependencies.iter() {
+            intersects.push(connection.dump_term(registry));
+            intersects.push(dep.dump_term(registry));
         }
 
-        if unions.is_empty() {
+        Term::intersect(intersects)
+    }
+}
+
+impl DumpTerm for Group {
+    fn dump_term(&self, registry: &mut AtomRegistry) -> Term {
+        if let Some(beta) = self.beta {
+            return self.dump_common_cause(beta, registry);
+        }
+
+        let members: Vec<Term> = self
+            .dependencies
+            .iter()
+            .map(|svc| svc.dump_term(registry))
+            .collect();
+
+        if members.is_empty() {
             Term::None
         } else {
-            Term::union(unions)
+            Term::at_least(self.quorum, members)
         }
     }
 }
 
+impl Group {
+    // dump_common_cause represents the group as the common cause atom and at
+    // least `quorum` independent atoms of the members. The member shouldn't be
+    // referred elsewhere, where its failures wouldn't be shared with the
+    // common cause atom.
+    fn dump_common_cause(&self, beta: f64, registry: &mut AtomRegistry) -> Term {
+        let (independent, common_cause, common_cause_sla) = self.common_cause_atoms(beta);
+
+        let members: Vec<Term> = self
+            .dependencies
+            .iter()
+            .map(|member| match member.as_ref() {
+                Service::KnownSLA { name, domains, .. } => in_domains(
+                    registry.new_atom(independent_atom(name), independent),
+                    domains,
+                    registry,
+                ),
+                _ => unreachable!("validated on construction"),
+            })
+            .collect();
+
+        Term::intersect(vec![
+            Term::atom(registry.new_atom(common_cause, common_cause_sla)),
+            Term::at_least(self.quorum, members),
+        ])
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use float_cmp::approx_eq;
     use rand::Rng;
 
     use crate::{
         calculate::{AtomRegistry, DumpTerm},
+        error::SlacError,
         sla::*,
     };
 
@@ -195,4 +813,130 @@ mod tests {
             assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64))
         }
     }
+
+    #[test]
+    fn test_program() {
+        let infra_a = Infra::new("infra_a", 0.9);
+        let infra_b = Infra::new("infra_b", 0.8);
+        let connection_a = Connection::new("connection_a", 0.7);
+        let connection_b = Connection::new("connection_b", 0.6);
+        let database = Service::known_sla("database", 0.5);
+
+        let api_a = Service::program(
+            "api_a",
+            infra_a,
+            vec![(connection_a, Dependency::Service(database.clone()))],
+        );
+        let api_b = Service::program(
+            "api_b",
+            infra_b,
+            vec![(connection_b, Dependency::Service(database))],
+        );
+        let group = Group::new(vec![api_a, api_b], 1);
+
+        let mut atom_registry = AtomRegistry::default();
+        let got = group.dump_term(&mut atom_registry).calc();
+
+        let (path_a, path_b) = (0.9 * 0.7, 0.8 * 0.6);
+        let expected = 0.5 * (path_a + path_b - path_a * path_b);
+        assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64))
+    }
+
+    #[test]
+    fn test_fault_domain() {
+        let region = FaultDomain::new("region", DomainKind::Region, 0.999, None);
+        let zone = FaultDomain::new("zone", DomainKind::Zone, 0.99, Some(region));
+
+        let replica_a = Service::in_domains("replica_a", 0.9, vec![zone.clone()]);
+        let replica_b = Service::in_domains("replica_b", 0.8, vec![zone]);
+        let group = Group::new(vec![replica_a, replica_b], 1);
+
+        // the replicas are in the same zone, so an outage of the zone or the
+        // region takes down both of them
+        let mut atom_registry = AtomRegistry::default();
+        let got = group.dump_term(&mut atom_registry).calc();
+        let expected = 0.999 * 0.99 * (1.0 - 0.1 * 0.2);
+        assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64))
+    }
+
+    #[test]
+    fn test_common_cause() {
+        let replicas: Vec<_> = (0..3)
+            .map(|index| Service::known_sla(format!("replica_{}", index), 0.99))
+            .collect();
+
+        let mut atom_registry = AtomRegistry::default();
+        let independent = Group::new(replicas.clone(), 2)
+            .dump_term(&mut atom_registry)
+            .calc();
+
+        let mut atom_registry = AtomRegistry::default();
+        let group = Group::with_common_cause(replicas, 2, 0.1);
+        assert!(group.validate().is_ok());
+        let got = group.dump_term(&mut atom_registry).calc();
+
+        // 10% of the failures are shared by all replicas
+        let p: f64 = 1.0 - 0.9 * 0.01;
+        let expected = (1.0 - 0.1 * 0.01) * (p.powi(3) + 3.0 * p.powi(2) * (1.0 - p));
+        assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64));
+        assert!(got < independent);
+
+        // a member referred outside of the group would fail independently of
+        // the common cause
+        let service = Service::dependencies(vec![
+            Dependency::Service(Service::known_sla("replica_0", 0.99)),
+            Dependency::Group(group),
+        ]);
+        assert_eq!(
+            service.validate(),
+            Err(SlacError::SharedCommonCauseMember {
+                name: "replica_0".to_string()
+            })
+        );
+
+        let members = vec![
+            Service::known_sla("replica_a", 0.99),
+            Service::known_sla("replica_b", 0.999),
+        ];
+        assert!(Group::try_with_common_cause(members, 1, 0.1).is_err());
+    }
+
+    #[test]
+    fn test_failure_model() {
+        let hours = |hours: u64| Duration::from_secs(hours * 3600);
+
+        let repairable = Service::modeled(
+            "repairable",
+            FailureModel::Repairable {
+                mtbf: hours(999),
+                mttr: hours(1),
+            },
+            Vec::new(),
+        );
+        let disk = Service::modeled(
+            "disk",
+            FailureModel::FailureRate { rate: 0.001 },
+            Vec::new(),
+        );
+        let service = Service::dependencies(vec![
+            Dependency::Service(repairable),
+            Dependency::Service(disk),
+        ]);
+
+        // the mission time is 30 days by default
+        let mut atom_registry = AtomRegistry::default();
+        let got = service.dump_term(&mut atom_registry).calc();
+        let expected = 0.999 * (-0.001f64 * 720.0).exp();
+        assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64));
+
+        let mut atom_registry = AtomRegistry::with_mission_time(hours(24));
+        let got = service.dump_term(&mut atom_registry).calc();
+        let expected = 0.999 * (-0.001f64 * 24.0).exp();
+        assert!(approx_eq!(f64, expected, got, epsilon = 0.0000001f64));
+
+        assert!(
+            Service::try_modeled("disk", FailureModel::FailureRate { rate: -1.0 }, Vec::new())
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,434 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::calculate::{AtomRegistry, Availability, DumpTerm, MONTH};
+
+use super::{Dependency, FaultDomain, Group, Infra, Service};
+
+// ServiceGraph is the dependency graph of the `sla` model, in which a shared
+// service, group, infra or fault domain is drawn only once, unlike the
+// flattened `Term`. Every node is annotated with its availability.
+pub struct ServiceGraph {
+    nodes: Vec<ServiceNode>,
+    edges: Vec<ServiceEdge>,
+
+    index: HashMap<Key, usize>,
+    names: HashMap<Key, String>,
+    connected: HashSet<(usize, usize, Option<String>)>,
+
+    // the period to derive the availability of services with a failure rate
+    mission_time: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Service,
+    Group,
+    Program,
+    Infra,
+    FaultDomain,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServiceNode {
+    label: String,
+    kind: NodeKind,
+    availability: f64,
+}
+
+impl ServiceNode {
+    /// Get a reference to the node's label.
+    pub fn label(&self) -> &str {
+        self.label.as_ref()
+    }
+
+    /// Get the node's kind.
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Get the node's availability.
+    pub fn availability(&self) -> f64 {
+        self.availability
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ServiceEdge {
+    source: usize,
+    target: usize,
+    // the connection through which a program reaches the dependency
+    label: Option<String>,
+}
+
+// Key identifies an object of the model by its address, and holds the `Arc`
+// so the address is not reused while the graph is alive
+#[derive(Clone)]
+enum Key {
+    Service(Arc<Service>),
+    Group(Arc<Group>),
+    Infra(Arc<Infra>),
+    FaultDomain(Arc<FaultDomain>),
+}
+
+impl Key {
+    fn address(&self) -> usize {
+        match self {
+            Key::Service(service) => Arc::as_ptr(service) as usize,
+            Key::Group(group) => Arc::as_ptr(group) as usize,
+            Key::Infra(infra) => Arc::as_ptr(infra) as usize,
+            Key::FaultDomain(domain) => Arc::as_ptr(domain) as usize,
+        }
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        self.address() == other.address()
+    }
+}
+
+impl Eq for Key {}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address().hash(state)
+    }
+}
+
+impl Default for ServiceGraph {
+    fn default() -> Self {
+        ServiceGraph::new(MONTH)
+    }
+}
+
+impl ServiceGraph {
+    pub fn new(mission_time: Duration) -> ServiceGraph {
+        ServiceGraph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            index: HashMap::new(),
+            names: HashMap::new(),
+            connected: HashSet::new(),
+            mission_time,
+        }
+    }
+
+    // name_service and name_group give a name to the services and groups
+    // which don't have one in the model, e.g. `Service::Dependencies`. They
+    // should be called before adding the services.
+    pub fn name_service<S: AsRef<str>>(&mut self, service: &Arc<Service>, name: S) {
+        self.names
+            .insert(Key::Service(service.clone()), name.as_ref().to_string());
+    }
+
+    pub fn name_group<S: AsRef<str>>(&mut self, group: &Arc<Group>, name: S) {
+        self.names
+            .insert(Key::Group(group.clone()), name.as_ref().to_string());
+    }
+
+    /// Get a reference to the node with the index.
+    pub fn node(&self, index: usize) -> &ServiceNode {
+        &self.nodes[index]
+    }
+
+    /// Get the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // availability calculates every node on its own with a fresh registry
+    fn availability<T: DumpTerm + ?Sized>(&self, object: &T) -> f64 {
+        object
+            .dump_term(&mut AtomRegistry::with_mission_time(self.mission_time))
+            .calc()
+    }
+
+    // insert returns the node of the object, and whether it's newly inserted
+    fn insert(&mut self, key: Key, label: String, kind: NodeKind) -> (usize, bool) {
+        if let Some(index) = self.index.get(&key) {
+            return (*index, false);
+        }
+
+        let availability = match &key {
+            Key::Service(service) => self.availability(service.as_ref()),
+            Key::Group(group) => self.availability(group.as_ref()),
+            Key::Infra(infra) => self.availability(infra.as_ref()),
+            Key::FaultDomain(domain) => self.availability(domain.as_ref()),
+        };
+        let label = match self.names.get(&key) {
+            Some(name) if name != &label => format!("{}\n{}", name, label),
+            _ => label,
+        };
+
+        self.nodes.push(ServiceNode {
+            label,
+            kind,
+            availability,
+        });
+        self.index.insert(key, self.nodes.len() - 1);
+        (self.nodes.len() - 1, true)
+    }
+
+    fn connect(&mut self, source: usize, target: usize, label: Option<String>) {
+        if self.connected.insert((source, target, label.clone())) {
+            self.edges.push(ServiceEdge {
+                source,
+                target,
+                label,
+            });
+        }
+    }
+
+    // add draws the service and everything it depends on, and returns its
+    // node
+    pub fn add(&mut self, service: &Arc<Service>) -> usize {
+        let key = Key::Service(service.clone());
+        let (index, inserted) = match service.as_ref() {
+            Service::KnownSLA { name, .. } | Service::Modeled { name, .. } => {
+                self.insert(key, name.clone(), NodeKind::Service)
+            }
+            Service::Dependencies(dependencies) => {
+                let label = self
+                    .names
+                    .get(&key)
+                    .cloned()
+                    .unwrap_or_else(|| format!("all of {}", dependencies.len()));
+                self.insert(key, label, NodeKind::Service)
+            }
+            Service::Program(program) => {
+                self.insert(key, program.name().to_string(), NodeKind::Program)
+            }
+        };
+        if !inserted {
+            return index;
+        }
+
+        match service.as_ref() {
+            Service::KnownSLA { domains, .. } | Service::Modeled { domains, .. } => {
+                self.add_domains(index, domains)
+            }
+            Service::Dependencies(dependencies) => {
+                for dependency in dependencies {
+                    let target = self.add_dependency(dependency);
+                    self.connect(index, target, None);
+                }
+            }
+            Service::Program(program) => {
+                let infra = self.add_infra(program.infra());
+                self.connect(index, infra, None);
+                for (connection, dependency) in program.dependencies() {
+                    let target = self.add_dependency(dependency);
+                    let label = format!("{} {}", connection.name(), percent(connection.sla()));
+                    self.connect(index, target, Some(label));
+                }
+            }
+        }
+        index
+    }
+
+    fn add_dependency(&mut self, dependency: &Dependency) -> usize {
+        match dependency {
+            Dependency::Service(service) => self.add(service),
+            Dependency::Group(group) => self.add_group(group),
+        }
+    }
+
+    fn add_group(&mut self, group: &Arc<Group>) -> usize {
+        let mut label = format!("{} of {}", group.quorum(), group.dependencies().len());
+        if let Some(beta) = group.beta() {
+            label = format!("{}, beta {}", label, beta);
+        }
+
+        let (index, inserted) = self.insert(Key::Group(group.clone()), label, NodeKind::Group);
+        if inserted {
+            for member in group.dependencies() {
+                let target = self.add(member);
+                self.connect(index, target, None);
+            }
+        }
+        index
+    }
+
+    fn add_infra(&mut self, infra: &Arc<Infra>) -> usize {
+        let (index, inserted) = self.insert(
+            Key::Infra(infra.clone()),
+            infra.name().to_string(),
+            NodeKind::Infra,
+        );
+        if inserted {
+            self.add_domains(index, infra.domains());
+        }
+        index
+    }
+
+    fn add_domains(&mut self, source: usize, domains: &[Arc<FaultDomain>]) {
+        for domain in domains {
+            let target = self.add_domain(domain);
+            self.connect(source, target, None);
+        }
+    }
+
+    fn add_domain(&mut self, domain: &Arc<FaultDomain>) -> usize {
+        let label = format!(
+            "{} {}",
+            format!("{:?}", domain.kind()).to_lowercase(),
+            domain.name()
+        );
+        let (index, inserted) = self.insert(
+            Key::FaultDomain(domain.clone()),
+            label,
+            NodeKind::FaultDomain,
+        );
+        if inserted {
+            if let Some(parent) = domain.parent() {
+                let target = self.add_domain(parent);
+                self.connect(index, target, None);
+            }
+        }
+        index
+    }
+}
+
+fn percent(availability: f64) -> String {
+    Availability::from_probability(availability).percent()
+}
+
+impl<'a> dot::Labeller<'a, usize, ServiceEdge> for ServiceGraph {
+    fn graph_id(&'a self) -> dot::Id<'a> {
+        dot::Id::new("services").unwrap()
+    }
+
+    fn node_id(&'a self, n: &usize) -> dot::Id<'a> {
+        dot::Id::new(format!("n{}", n)).unwrap()
+    }
+
+    fn node_label(&'a self, n: &usize) -> dot::LabelText<'a> {
+        let node = &self.nodes[*n];
+        dot::LabelText::LabelStr(Cow::Owned(format!(
+            "{}\n{}",
+            node.label,
+            percent(node.availability)
+        )))
+    }
+
+    fn node_shape(&'a self, n: &usize) -> Option<dot::LabelText<'a>> {
+        let shape = match self.nodes[*n].kind {
+            NodeKind::Service => "box",
+            NodeKind::Group => "hexagon",
+            NodeKind::Program => "component",
+            NodeKind::Infra => "box3d",
+            NodeKind::FaultDomain => "folder",
+        };
+        Some(dot::LabelText::LabelStr(Cow::Borrowed(shape)))
+    }
+
+    fn edge_label(&'a self, e: &ServiceEdge) -> dot::LabelText<'a> {
+        dot::LabelText::LabelStr(Cow::Owned(e.label.clone().unwrap_or_default()))
+    }
+}
+
+impl<'a> dot::GraphWalk<'a, usize, ServiceEdge> for ServiceGraph {
+    fn nodes(&'a self) -> dot::Nodes<'a, usize> {
+        Cow::Owned((0..self.nodes.len()).collect())
+    }
+
+    fn edges(&'a self) -> dot::Edges<'a, ServiceEdge> {
+        Cow::Borrowed(&self.edges)
+    }
+
+    fn source(&'a self, edge: &ServiceEdge) -> usize {
+        edge.source
+    }
+
+    fn target(&'a self, edge: &ServiceEdge) -> usize {
+        edge.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sla::{Connection, Dependency, DomainKind, FaultDomain, Group, Infra, Service};
+
+    use super::ServiceGraph;
+
+    #[test]
+    fn test_service_graph() {
+        let zone = FaultDomain::new("a", DomainKind::Zone, 0.999, None);
+        let database = Service::in_domains("database", 0.999, vec![zone.clone()]);
+        let replicas: Vec<_> = (0..4)
+            .map(|index| {
+                Service::dependencies(vec![
+                    Dependency::Service(database.clone()),
+                    Dependency::Service(Service::known_sla(format!("replica_{}", index), 0.99)),
+                ])
+            })
+            .collect();
+        let group = Group::new(replicas, 2);
+        let program = Service::program(
+            "api",
+            Infra::in_domains("machine", 0.99, vec![zone]),
+            vec![(
+                Connection::new("lan", 0.9999),
+                Dependency::Group(group.clone()),
+            )],
+        );
+
+        let mut graph = ServiceGraph::default();
+        graph.name_group(&group, "replicas");
+        let root = graph.add(&program);
+        // api, machine, zone, the group, 4 replicas with their own services,
+        // and the database
+        assert_eq!(graph.len(), 13);
+        assert_eq!(graph.node(root).label(), "api");
+        assert!(graph
+            .nodes
+            .iter()
+            .any(|node| node.label() == "replicas\n2 of 4"));
+
+        // the database is shared by the replicas, and drawn once with an edge
+        // from every replica
+        let database = graph
+            .nodes
+            .iter()
+            .position(|node| node.label() == "database")
+            .unwrap();
+        assert_eq!(
+            graph
+                .edges
+                .iter()
+                .filter(|edge| edge.target == database)
+                .count(),
+            4
+        );
+
+        let mut output = Vec::new();
+        dot::render(&graph, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("label=\"lan 99.990%\""));
+    }
+}
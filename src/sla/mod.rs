@@ -14,5 +14,8 @@
 //
 
 mod definition;
+mod graph;
+mod validate;
 
 pub use definition::*;
+pub use graph::*;
@@ -0,0 +1,222 @@
+// Copyright 2022 Chaos Mesh Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use crate::{
+    calculate::MONTH,
+    error::{check_sla, SlacError},
+};
+
+use super::{check_beta, check_quorum, independent_atom, Dependency, FaultDomain, Group, Service};
+
+// Validator walks through the model, and checks that every sla is valid and
+// every name refers to the same sla
+#[derive(Default)]
+struct Validator {
+    slas: HashMap<String, f64>,
+
+    // shared services and groups are only validated once
+    visited: HashSet<usize>,
+
+    // the services referred directly, and the members of common cause groups,
+    // which are represented differently and shouldn't overlap
+    services: HashSet<String>,
+    members: HashSet<String>,
+}
+
+impl Validator {
+    fn sla(&mut self, name: &str, sla: f64) -> Result<(), SlacError> {
+        check_sla(name, sla)?;
+
+        let existing = *self.slas.entry(name.to_string()).or_insert(sla);
+        if existing != sla {
+            return Err(SlacError::ConflictingAtom {
+                name: name.to_string(),
+                existing,
+                requested: sla,
+            });
+        }
+        Ok(())
+    }
+
+    fn domains(&mut self, domains: &[Arc<FaultDomain>]) -> Result<(), SlacError> {
+        for domain in domains {
+            let mut domain = Some(domain);
+            while let Some(current) = domain {
+                self.sla(current.name(), current.sla())?;
+                domain = current.parent();
+            }
+        }
+        Ok(())
+    }
+
+    fn service(&mut self, service: &Service) -> Result<(), SlacError> {
+        if !self.visited.insert(service as *const Service as usize) {
+            return Ok(());
+        }
+
+        match service {
+            Service::KnownSLA {
+                name, sla, domains, ..
+            } => {
+                if self.members.contains(name) {
+                    return Err(SlacError::SharedCommonCauseMember { name: name.clone() });
+                }
+                self.services.insert(name.clone());
+                self.sla(name, *sla)?;
+                self.domains(domains)
+            }
+            Service::Modeled {
+                name,
+                model,
+                domains,
+            } => {
+                // the probability depends on the mission time, but the same
+                // model always results in the same probability
+                model.check(name)?;
+                self.sla(name, model.probability(MONTH))?;
+                self.domains(domains)
+            }
+            Service::Dependencies(dependencies) if dependencies.is_empty() => {
+                Err(SlacError::EmptyDependencies)
+            }
+            Service::Dependencies(dependencies) => {
+                for dep in dependencies {
+                    self.dependency(dep)?;
+                }
+                Ok(())
+            }
+            Service::Program(program) => {
+                self.sla(program.infra().name(), program.infra().sla())?;
+                self.domains(program.infra().domains())?;
+                for (connection, dep) in program.dependencies() {
+                    self.sla(connection.name(), connection.sla())?;
+                    self.dependency(dep)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn group(&mut self, group: &Group) -> Result<(), SlacError> {
+        if !self.visited.insert(group as *const Group as usize) {
+            return Ok(());
+        }
+
+        check_quorum(group.quorum(), group.dependencies().len())?;
+
+        let beta = match group.beta() {
+            Some(beta) => beta,
+            None => {
+                for svc in group.dependencies() {
+                    self.service(svc)?;
+                }
+                return Ok(());
+            }
+        };
+
+        // a deserialized group skips the checks of its constructor
+        check_beta(beta)?;
+        group.common_cause_sla()?;
+
+        // the members of a common cause group are represented by their
+        // independent atoms and the common cause atom
+        let (independent, common_cause, common_cause_sla) = group.common_cause_atoms(beta);
+        self.sla(&common_cause, common_cause_sla)?;
+        for svc in group.dependencies() {
+            if let Service::KnownSLA { name, domains, .. } = svc.as_ref() {
+                if self.services.contains(name) || !self.members.insert(name.clone()) {
+                    return Err(SlacError::SharedCommonCauseMember { name: name.clone() });
+                }
+                self.sla(&independent_atom(name), independent)?;
+                self.domains(domains)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn dependency(&mut self, dependency: &Dependency) -> Result<(), SlacError> {
+        match dependency {
+            Dependency::Service(svc) => self.service(svc),
+            Dependency::Group(group) => self.group(group),
+        }
+    }
+}
+
+impl Service {
+    // validate checks the service and everything it depends on, so that
+    // dumping its term never panics
+    pub fn validate(&self) -> Result<(), SlacError> {
+        Validator::default().service(self)
+    }
+}
+
+impl Group {
+    pub fn validate(&self) -> Result<(), SlacError> {
+        Validator::default().group(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::SlacError,
+        sla::{Dependency, Group, Infra, Service},
+    };
+
+    #[test]
+    fn test_validate() {
+        assert!(matches!(
+            Service::try_known_sla("infra", 1.5),
+            Err(SlacError::InvalidSla { .. })
+        ));
+        assert!(matches!(
+            Infra::try_new("infra", f64::NAN),
+            Err(SlacError::InvalidSla { .. })
+        ));
+
+        let infra = Service::known_sla("infra", 0.9);
+        assert_eq!(
+            Group::try_new(vec![infra.clone()], 2).err(),
+            Some(SlacError::InvalidQuorum {
+                quorum: 2,
+                members: 1
+            })
+        );
+        assert!(Group::try_new(vec![infra.clone()], 0).is_err());
+
+        let service = Service::dependencies(vec![
+            Dependency::Service(infra),
+            Dependency::Service(Service::known_sla("infra", 0.99)),
+        ]);
+        assert_eq!(
+            service.validate(),
+            Err(SlacError::ConflictingAtom {
+                name: "infra".to_string(),
+                existing: 0.9,
+                requested: 0.99,
+            })
+        );
+
+        assert_eq!(
+            Service::dependencies(vec![]).validate(),
+            Err(SlacError::EmptyDependencies)
+        );
+    }
+}